@@ -0,0 +1,201 @@
+// Decode and execute throughput. CI compares pull requests against a baseline
+// of their base branch, locally save one before a redesign and compare
+// against it afterwards:
+//
+//   cargo bench --bench throughput -- --save-baseline before
+//   cargo bench --bench throughput -- --baseline before
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+
+use chip8_rs::emulator::instruction::parser::NomDecoder;
+use chip8_rs::emulator::instruction::table::TableDecoder;
+use chip8_rs::emulator::instruction::Decoder;
+use chip8_rs::emulator::platform::headless::HeadlessPlatform;
+use chip8_rs::emulator::platform::*;
+use chip8_rs::emulator::Emulator;
+
+const FRAMES: u32 = 60;
+const STRAIGHT_LINE_LENGTH: u64 = 1000;
+
+// Tight arithmetic loop
+const ALU_ROM: &[u8] = &[
+    0x60, 0x01, // V0 = 1
+    0x70, 0x01, // V0 += 1
+    0x80, 0x14, // V0 += V1
+    0x81, 0x05, // V1 -= V0
+    0x82, 0x06, // V2 >>= 1
+    0x83, 0x12, // V3 &= V1
+    0x12, 0x02, // jump 0x202
+];
+
+// Fills the display with font sprites over and over
+const DRAW_ROM: &[u8] = &[
+    0x00, 0xE0, // clear
+    0xA0, 0x50, // I = font "0"
+    0x61, 0x00, // V1 = 0
+    0x60, 0x00, // V0 = 0
+    0xD0, 0x15, // draw 5 rows at V0, V1
+    0x70, 0x08, // V0 += 8
+    0x30, 0x40, // skip if V0 == 64
+    0x12, 0x08, // jump 0x208
+    0x71, 0x05, // V1 += 5
+    0x31, 0x1E, // skip if V1 == 30
+    0x12, 0x06, // jump 0x206
+    0x12, 0x00, // jump 0x200
+];
+
+// Memory, font and random instructions
+const MEMORY_ROM: &[u8] = &[
+    0xA3, 0x00, // I = 0x300
+    0x60, 0x7B, // V0 = 123
+    0xF0, 0x33, // BCD of V0
+    0xF2, 0x65, // load V0..V2
+    0xF2, 0x55, // store V0..V2
+    0xF0, 0x29, // I = font for V0
+    0xC3, 0xFF, // V3 = random
+    0x12, 0x00, // jump 0x200
+];
+
+// The IBM logo test ROM, draws six sprites and then jumps in place
+const IBM_LOGO_ROM: &[u8] = include_bytes!("../src/emulator/test-dependencies/ibm-logo.ch8");
+
+// Does nothing, so only the emulator itself is measured
+struct NullPlatform;
+
+impl Platform for NullPlatform {
+    async fn get_display_width(&self) -> u8 {
+        64
+    }
+    async fn get_display_height(&self) -> u8 {
+        32
+    }
+    async fn clear_display(&mut self) {}
+    async fn get_pixel(&self, _pixel: Pixel) -> PixelState {
+        PixelState::Off
+    }
+    async fn set_pixel(&mut self, _pixel: Pixel, _state: PixelState) {}
+    async fn read_keypress_state(&self, _key: KeypadNumber) -> KeyState {
+        KeyState::Off
+    }
+    async fn set_buzzer(&mut self, _state: BuzzerState) {}
+    async fn random_byte(&mut self) -> u8 {
+        0x5A
+    }
+    async fn wait_for_next_frame(&mut self) {}
+}
+
+fn decode(c: &mut Criterion) {
+    let decoders: [(&str, &dyn Decoder); 2] = [("nom", &NomDecoder), ("table", &TableDecoder)];
+
+    for (decoder_name, decoder) in decoders {
+        let mut group = c.benchmark_group(format!("decode/{decoder_name}"));
+        group.throughput(Throughput::Elements(0x1000));
+
+        for family in 0..=0xFu8 {
+            let opcodes: Vec<[u8; 2]> = (0..0x1000u16)
+                .map(|operands| {
+                    let opcode = (family as u16) << 12 | operands;
+                    opcode.to_be_bytes()
+                })
+                .collect();
+
+            group.bench_with_input(
+                BenchmarkId::from_parameter(format!("{family:X}NNN")),
+                &opcodes,
+                |b, opcodes| {
+                    b.iter(|| {
+                        for opcode in opcodes {
+                            black_box(decoder.decode(black_box(opcode)));
+                        }
+                    })
+                },
+            );
+        }
+        group.finish();
+    }
+}
+
+fn loaded_emulator<PLATFORM: Platform>(platform: PLATFORM, rom: &[u8]) -> Emulator<PLATFORM> {
+    let mut emulator = Emulator::new(platform);
+    async_io::block_on(emulator.load_into_memory(rom)).unwrap();
+    emulator
+}
+
+// Straight line code, so that the first pass decodes every instruction and
+// later passes only hit the decode cache
+fn straight_line_rom() -> Vec<u8> {
+    let mut rom = [0x70, 0x01].repeat(STRAIGHT_LINE_LENGTH as usize); // V0 += 1
+    rom.extend_from_slice(&[0x12, 0x00]); // jump 0x200
+    rom
+}
+
+// One pass over the program in a fresh emulator against a second pass
+fn decode_cache(c: &mut Criterion) {
+    let rom = straight_line_rom();
+    let cycles = STRAIGHT_LINE_LENGTH + 1;
+
+    let mut group = c.benchmark_group("decode_cache");
+    group.throughput(Throughput::Elements(cycles));
+    for (name, warm) in [("cold", false), ("warm", true)] {
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || {
+                    let mut emulator = loaded_emulator(NullPlatform, &rom);
+                    if warm {
+                        async_io::block_on(emulator.run_cycles(cycles)).1.unwrap();
+                    }
+                    emulator
+                },
+                |mut emulator| {
+                    async_io::block_on(emulator.run_cycles(cycles)).1.unwrap();
+                    emulator
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+// Every iteration starts from a freshly loaded ROM, so that ROMs which end in
+// a jump to themselves are measured from the start each time
+fn run_frames<PLATFORM: Platform>(c: &mut Criterion, group_name: &str, platform: fn() -> PLATFORM) {
+    let mut group = c.benchmark_group(group_name);
+    let roms = [
+        ("alu", ALU_ROM),
+        ("draw", DRAW_ROM),
+        ("memory", MEMORY_ROM),
+        ("ibm_logo", IBM_LOGO_ROM),
+    ];
+
+    for (name, rom) in roms {
+        let instructions =
+            Emulator::new(platform()).instructions_per_frame() as u64 * FRAMES as u64;
+        group.throughput(Throughput::Elements(instructions));
+
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || loaded_emulator(platform(), rom),
+                |mut emulator| {
+                    async_io::block_on(async {
+                        for _ in 0..FRAMES {
+                            emulator.run_frame().await.unwrap();
+                        }
+                    });
+                    emulator
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn execute(c: &mut Criterion) {
+    run_frames(c, "execute/null", || NullPlatform);
+    run_frames(c, "execute/headless", || HeadlessPlatform::new(1));
+}
+
+criterion_group!(benches, decode, decode_cache, execute);
+criterion_main!(benches);
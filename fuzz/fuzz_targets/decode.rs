@@ -0,0 +1,18 @@
+// Every pair of bytes is decoded by both decoders, which must agree
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use chip8_rs::emulator::instruction::parser::{parse_instruction, NomDecoder};
+use chip8_rs::emulator::instruction::table::TableDecoder;
+use chip8_rs::emulator::instruction::Decoder;
+
+fuzz_target!(|data: &[u8]| {
+    for opcode in data.chunks_exact(2) {
+        let opcode: &[u8; 2] = opcode.try_into().unwrap();
+        let decoded = parse_instruction(opcode);
+
+        assert_eq!(decoded, NomDecoder.decode(opcode));
+        assert_eq!(decoded, TableDecoder.decode(opcode));
+    }
+});
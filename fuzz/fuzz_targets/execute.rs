@@ -0,0 +1,81 @@
+// Runs arbitrary ROMs, anything wrong with them has to come back as a Fault
+// rather than a panic. Seed the corpus with the test ROMs, the test suite ones
+// need the chip8-test-suite submodule:
+//
+//   cargo +nightly fuzz run execute fuzz/corpus/execute src/emulator/test-dependencies \
+//       src/emulator/test-dependencies/chip8-test-suite/bin
+#![no_main]
+
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+use libfuzzer_sys::fuzz_target;
+
+use chip8_rs::emulator::platform::*;
+use chip8_rs::emulator::{Emulator, MAX_ROM_SIZE};
+
+const CYCLES: usize = 10_000;
+
+// Even keys held down and odd ones up, so that key skips take both paths. A
+// key wait never ends since the pressed key is never released
+struct NullPlatform {
+    random_state: u8,
+}
+
+impl Platform for NullPlatform {
+    async fn get_display_width(&self) -> u8 {
+        64
+    }
+    async fn get_display_height(&self) -> u8 {
+        32
+    }
+    async fn clear_display(&mut self) {}
+    async fn get_pixel(&self, _pixel: Pixel) -> PixelState {
+        PixelState::On
+    }
+    async fn set_pixel(&mut self, _pixel: Pixel, _state: PixelState) {}
+    async fn read_keypress_state(&self, key: KeypadNumber) -> KeyState {
+        if key.0 % 2 == 0 {
+            KeyState::On
+        } else {
+            KeyState::Off
+        }
+    }
+    async fn set_buzzer(&mut self, _state: BuzzerState) {}
+    async fn random_byte(&mut self) -> u8 {
+        self.random_state = self.random_state.wrapping_mul(31).wrapping_add(7);
+        self.random_state
+    }
+    async fn wait_for_next_frame(&mut self) {}
+}
+
+// The platform never suspends, so polling until ready does not spin
+fn run_to_completion<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+fuzz_target!(|rom: &[u8]| {
+    if rom.len() > MAX_ROM_SIZE {
+        return;
+    }
+
+    let mut emulator = Emulator::new(NullPlatform { random_state: 0 });
+    run_to_completion(async {
+        emulator.load_into_memory(rom).await.unwrap();
+        for cycle in 0..CYCLES {
+            if emulator.run_instruction_loop().await.is_err() {
+                break;
+            }
+            if cycle % emulator.instructions_per_frame() as usize == 0 {
+                emulator.handle_timers().await;
+            }
+        }
+    });
+});
@@ -0,0 +1,140 @@
+mod tests;
+
+use crate::emulator::platform::{BuzzerState, TIMER_FREQUENCY};
+
+pub const DEFAULT_SAMPLE_RATE: u32 = 44_100;
+pub const DEFAULT_FREQUENCY: f32 = 440.0;
+pub const DEFAULT_AMPLITUDE: f32 = 0.25;
+// Long enough to avoid clicks without smearing short beeps
+pub const DEFAULT_RAMP_MILLISECONDS: f32 = 5.0;
+
+pub struct SquareWave {
+    sample_rate: u32,
+    frequency: f32,
+    amplitude: f32,
+    ramp_milliseconds: f32,
+
+    phase: f32,
+    envelope: f32,
+}
+
+impl SquareWave {
+    pub fn new(sample_rate: u32, frequency: f32) -> Self {
+        SquareWave {
+            sample_rate,
+            frequency,
+            amplitude: DEFAULT_AMPLITUDE,
+            ramp_milliseconds: DEFAULT_RAMP_MILLISECONDS,
+            phase: 0.0,
+            envelope: 0.0,
+        }
+    }
+
+    pub fn with_amplitude(mut self, amplitude: f32) -> Self {
+        self.amplitude = amplitude;
+        self
+    }
+
+    pub fn with_ramp_milliseconds(mut self, ramp_milliseconds: f32) -> Self {
+        self.ramp_milliseconds = ramp_milliseconds;
+        self
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn samples_per_frame(&self) -> usize {
+        (self.sample_rate / TIMER_FREQUENCY) as usize
+    }
+
+    // The same length of time at any sample rate
+    fn ramp_samples(&self) -> u32 {
+        (self.sample_rate as f32 * self.ramp_milliseconds / 1000.0) as u32
+    }
+
+    pub fn fill(&mut self, state: BuzzerState, buffer: &mut [f32]) {
+        let target = match state {
+            BuzzerState::On => 1.0,
+            BuzzerState::Off => 0.0,
+        };
+        let ramp_samples = self.ramp_samples();
+        let envelope_step = if ramp_samples == 0 {
+            1.0
+        } else {
+            1.0 / ramp_samples as f32
+        };
+        let phase_step = self.frequency / self.sample_rate as f32;
+
+        for sample in buffer {
+            // Ramp towards the target instead of jumping, the discontinuity is what clicks
+            if self.envelope < target {
+                self.envelope = (self.envelope + envelope_step).min(target);
+            } else if self.envelope > target {
+                self.envelope = (self.envelope - envelope_step).max(target);
+            }
+
+            let level = if self.phase < 0.5 { 1.0 } else { -1.0 };
+            *sample = level * self.amplitude * self.envelope;
+
+            self.phase += phase_step;
+            if self.phase >= 1.0 {
+                self.phase -= 1.0;
+            }
+        }
+    }
+}
+
+impl Default for SquareWave {
+    fn default() -> Self {
+        SquareWave::new(DEFAULT_SAMPLE_RATE, DEFAULT_FREQUENCY)
+    }
+}
+
+// Offline rendering, one buzzer state per 60 Hz frame
+#[cfg(feature = "std")]
+pub struct AudioRecorder {
+    wave: SquareWave,
+    samples: Vec<f32>,
+}
+
+#[cfg(feature = "std")]
+impl AudioRecorder {
+    pub fn new(wave: SquareWave) -> Self {
+        AudioRecorder {
+            wave,
+            samples: vec![],
+        }
+    }
+
+    pub fn record_frame(&mut self, state: BuzzerState) {
+        let start = self.samples.len();
+        self.samples
+            .resize(start + self.wave.samples_per_frame(), 0.0);
+        self.wave.fill(state, &mut self.samples[start..]);
+    }
+
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    pub fn write_wav<W: std::io::Write + std::io::Seek>(&self, writer: W) -> hound::Result<()> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: self.wave.sample_rate(),
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+
+        let mut wav_writer = hound::WavWriter::new(writer, spec)?;
+        for sample in &self.samples {
+            wav_writer.write_sample((sample * i16::MAX as f32) as i16)?;
+        }
+        wav_writer.finalize()
+    }
+
+    pub fn save_wav<P: AsRef<std::path::Path>>(&self, path: P) -> hound::Result<()> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_wav(file)
+    }
+}
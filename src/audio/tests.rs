@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod test {
+    use crate::audio::*;
+
+    #[test]
+    fn silent_when_buzzer_off() {
+        let mut wave = SquareWave::new(8_000, 1_000.0);
+        let mut buffer = [1.0; 64];
+
+        wave.fill(BuzzerState::Off, &mut buffer);
+
+        assert!(buffer.iter().all(|&sample| sample == 0.0));
+    }
+
+    #[test]
+    fn square_wave_period_matches_frequency() {
+        let mut wave = SquareWave::new(8_000, 1_000.0)
+            .with_amplitude(1.0)
+            .with_ramp_milliseconds(0.0);
+        let mut buffer = [0.0; 16];
+
+        wave.fill(BuzzerState::On, &mut buffer);
+
+        // 8 samples per period, half high and half low
+        let expected = [1.0, 1.0, 1.0, 1.0, -1.0, -1.0, -1.0, -1.0];
+        assert_eq!(buffer[0..8], expected);
+        assert_eq!(buffer[8..16], expected);
+    }
+
+    #[test]
+    fn envelope_ramps_in_and_out() {
+        let mut wave = SquareWave::new(8_000, 1_000.0)
+            .with_amplitude(1.0)
+            .with_ramp_milliseconds(0.5);
+        let mut attack = [0.0; 4];
+        let mut release = [0.0; 4];
+
+        wave.fill(BuzzerState::On, &mut attack);
+        wave.fill(BuzzerState::Off, &mut release);
+
+        assert_eq!(attack, [0.25, 0.5, 0.75, 1.0]);
+        assert_eq!(release, [-0.75, -0.5, -0.25, 0.0]);
+    }
+
+    #[test]
+    fn ramp_length_follows_the_sample_rate() {
+        assert_eq!(SquareWave::new(44_100, 440.0).ramp_samples(), 220);
+        assert_eq!(SquareWave::new(48_000, 440.0).ramp_samples(), 240);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn recorder_renders_one_frame_per_state() {
+        let mut recorder = AudioRecorder::new(SquareWave::new(6_000, 1_000.0));
+
+        recorder.record_frame(BuzzerState::On);
+        recorder.record_frame(BuzzerState::Off);
+
+        assert_eq!(recorder.samples().len(), 200);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn wav_export_round_trips() {
+        let mut recorder = AudioRecorder::new(SquareWave::new(6_000, 1_000.0));
+        recorder.record_frame(BuzzerState::On);
+
+        let mut wav = std::io::Cursor::new(vec![]);
+        recorder.write_wav(&mut wav).unwrap();
+        wav.set_position(0);
+
+        let reader = hound::WavReader::new(wav).unwrap();
+        assert_eq!(reader.spec().sample_rate, 6_000);
+        assert_eq!(reader.spec().channels, 1);
+        assert_eq!(reader.len(), 100);
+    }
+}
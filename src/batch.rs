@@ -0,0 +1,445 @@
+// Runs a directory of ROMs on worker threads for regression testing. A
+// manifest.toml in the directory sets defaults at the top level and per-ROM
+// settings and expectations by file name:
+//
+//   cycles = 100000
+//
+//   [rom."ibm_logo.ch8"]
+//   cycles = 200
+//   hash = "4b3f0c1e9a7d2256"
+//   quirks = { vblank = true }
+//   inputs = [
+//       { cycle = 100, key = 0x5, pressed = true },
+//       { cycle = 150, key = 0x5, pressed = false },
+//   ]
+//
+// ROMs without an entry run with the defaults and pass unless they fault.
+mod tests;
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use derive_more::From;
+use serde::{Deserialize, Serialize};
+
+use crate::emulator::platform::headless::HeadlessPlatform;
+use crate::emulator::platform::{KeyState, KeypadNumber, KEYPAD_COUNT};
+use crate::emulator::quirks::Quirks;
+use crate::emulator::{Emulator, MAX_ROM_SIZE};
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.toml";
+pub const DEFAULT_CYCLES: u64 = 100_000;
+
+#[derive(Debug, From)]
+pub enum BatchError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    #[from(skip)]
+    Invalid(String),
+}
+
+impl std::fmt::Display for BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatchError::Io(error) => write!(f, "could not read batch: {error}"),
+            BatchError::Toml(error) => write!(f, "invalid manifest: {error}"),
+            BatchError::Invalid(message) => write!(f, "invalid manifest: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+#[derive(Debug, PartialEq, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Input {
+    pub cycle: u64,
+    pub key: u8,
+    pub pressed: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSpec {
+    cycles: Option<u64>,
+    tickrate: Option<u32>,
+    seed: Option<u32>,
+    #[serde(default)]
+    quirks: BTreeMap<String, bool>,
+    hash: Option<String>,
+    #[serde(default)]
+    inputs: Vec<Input>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RomSpec {
+    pub cycles: u64,
+    pub tickrate: Option<u32>,
+    pub seed: u32,
+    pub quirks: Quirks,
+    // Framebuffer::hash once every cycle has run
+    pub expected_hash: Option<u64>,
+    // In cycle order
+    pub inputs: Vec<Input>,
+}
+
+impl Default for RomSpec {
+    fn default() -> Self {
+        RomSpec {
+            cycles: DEFAULT_CYCLES,
+            tickrate: None,
+            seed: 1,
+            quirks: Quirks::default(),
+            expected_hash: None,
+            inputs: Vec::new(),
+        }
+    }
+}
+
+impl RomSpec {
+    fn merged(&self, raw: &RawSpec) -> Result<RomSpec, BatchError> {
+        let mut quirks = self.quirks;
+        for (name, value) in &raw.quirks {
+            let quirk = quirks
+                .by_name_mut(name)
+                .ok_or_else(|| BatchError::Invalid(format!("unknown quirk {name}")))?;
+            *quirk = *value;
+        }
+
+        let expected_hash = match &raw.hash {
+            Some(hash) => Some(
+                u64::from_str_radix(hash, 16)
+                    .map_err(|_| BatchError::Invalid(format!("{hash} is not a hex hash")))?,
+            ),
+            None => self.expected_hash,
+        };
+
+        if let Some(input) = raw.inputs.iter().find(|input| input.key >= KEYPAD_COUNT) {
+            return Err(BatchError::Invalid(format!(
+                "{} is not a keypad key",
+                input.key
+            )));
+        }
+        let mut inputs = raw.inputs.clone();
+        inputs.sort_by_key(|input| input.cycle);
+
+        Ok(RomSpec {
+            cycles: raw.cycles.unwrap_or(self.cycles),
+            tickrate: raw.tickrate.or(self.tickrate),
+            seed: raw.seed.unwrap_or(self.seed),
+            quirks,
+            expected_hash,
+            inputs,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Manifest {
+    pub defaults: RomSpec,
+    pub roms: BTreeMap<String, RomSpec>,
+}
+
+impl Manifest {
+    pub fn from_toml(text: &str) -> Result<Self, BatchError> {
+        // Split off [rom.*] first, so that unknown top level keys are rejected
+        let mut table: toml::Table = toml::from_str(text)?;
+        let raw_roms: BTreeMap<String, RawSpec> = match table.remove("rom") {
+            Some(roms) => roms.try_into()?,
+            None => BTreeMap::new(),
+        };
+        let defaults = RomSpec::default().merged(&table.try_into()?)?;
+        let roms = raw_roms
+            .iter()
+            .map(|(name, rom)| Ok((name.clone(), defaults.merged(rom)?)))
+            .collect::<Result<_, BatchError>>()?;
+        Ok(Manifest { defaults, roms })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, BatchError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn spec(&self, name: &str) -> &RomSpec {
+        self.roms.get(name).unwrap_or(&self.defaults)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub name: String,
+    // Err for ROMs named in the manifest that could not be read
+    pub rom: Result<Vec<u8>, String>,
+    pub spec: RomSpec,
+}
+
+// Every file in the directory except the manifest and hidden files, plus any
+// manifest entries without a file, sorted by name
+pub fn collect_jobs<P: AsRef<Path>>(directory: P, manifest: &Manifest) -> io::Result<Vec<Job>> {
+    let mut files = BTreeMap::new();
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name == MANIFEST_FILE_NAME || name.starts_with('.') || !entry.file_type()?.is_file() {
+            continue;
+        }
+        files.insert(name, entry.path());
+    }
+    for name in manifest.roms.keys() {
+        files.entry(name.clone()).or_default();
+    }
+
+    let jobs = files
+        .into_iter()
+        .map(|(name, path)| {
+            let rom = std::fs::read(&path).map_err(|error| format!("could not read ROM: {error}"));
+            let spec = manifest.spec(&name).clone();
+            Job { name, rom, spec }
+        })
+        .collect();
+    Ok(jobs)
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "snake_case", tag = "status", content = "message")]
+pub enum Outcome {
+    Passed,
+    // Ran, but the screen did not match
+    Failed(String),
+    // Faulted or could not run
+    Error(String),
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct JobResult {
+    pub name: String,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+    pub cycles: u64,
+    #[serde(serialize_with = "serialize_hash")]
+    pub hash: u64,
+    #[serde(serialize_with = "serialize_seconds", rename = "seconds")]
+    pub duration: Duration,
+}
+
+fn serialize_hash<S: serde::Serializer>(hash: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{hash:016x}"))
+}
+
+fn serialize_seconds<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+pub fn run_job(job: &Job) -> JobResult {
+    let start = Instant::now();
+    let mut result = JobResult {
+        name: job.name.clone(),
+        outcome: Outcome::Passed,
+        cycles: 0,
+        hash: 0,
+        duration: Duration::ZERO,
+    };
+    // A panic is reported like a fault, so one ROM cannot stop the others
+    let run = panic::catch_unwind(AssertUnwindSafe(|| {
+        run_emulator(job, &mut result.cycles, &mut result.hash)
+    }));
+    result.outcome = match run.unwrap_or_else(|panic| Err(panic_message(panic))) {
+        Ok(()) => match job.spec.expected_hash {
+            Some(expected) if expected != result.hash => Outcome::Failed(format!(
+                "framebuffer hash {:016x}, expected {expected:016x}",
+                result.hash
+            )),
+            _ => Outcome::Passed,
+        },
+        Err(message) => Outcome::Error(message),
+    };
+    result.duration = start.elapsed();
+    result
+}
+
+fn panic_message(panic: Box<dyn std::any::Any + Send>) -> String {
+    let message = match (panic.downcast_ref::<&str>(), panic.downcast_ref::<String>()) {
+        (Some(message), _) => message,
+        (_, Some(message)) => message.as_str(),
+        _ => "no message",
+    };
+    format!("panicked: {message}")
+}
+
+// Cycles is left at the number that completed, including when it faults
+fn run_emulator(job: &Job, cycles: &mut u64, hash: &mut u64) -> Result<(), String> {
+    let rom = job.rom.as_ref()?;
+    if rom.len() > MAX_ROM_SIZE {
+        return Err(format!(
+            "ROM is {} bytes, at most {MAX_ROM_SIZE} fit",
+            rom.len()
+        ));
+    }
+
+    let spec = &job.spec;
+    let mut emulator = Emulator::new(HeadlessPlatform::new(spec.seed));
+    emulator.set_quirks(spec.quirks);
+    if let Some(tickrate) = spec.tickrate {
+        emulator.set_instructions_per_frame(tickrate);
+    }
+
+    // Headless platform futures are always ready
+    async_io::block_on(async {
+        emulator
+            .load_into_memory(rom)
+            .await
+            .map_err(|fault| fault.to_string())?;
+
+        let inputs = spec.inputs.iter().filter(|input| input.cycle < spec.cycles);
+        for input in inputs.chain([&Input {
+            cycle: spec.cycles,
+            key: 0,
+            pressed: false,
+        }]) {
+            let (completed, result) = emulator.run_cycles(input.cycle - *cycles).await;
+            *cycles += completed;
+            *hash = emulator.platform().framebuffer().hash();
+            result.map_err(|fault| fault.to_string())?;
+            if *cycles < spec.cycles {
+                let state = if input.pressed {
+                    KeyState::On
+                } else {
+                    KeyState::Off
+                };
+                emulator
+                    .platform_mut()
+                    .set_key(KeypadNumber(input.key), state);
+            }
+        }
+        Ok(())
+    })
+}
+
+// Fails the build, not just a test, if emulators can no longer move between
+// worker threads
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+    assert_send::<Emulator<HeadlessPlatform>>();
+};
+
+// Results come back in job order
+pub fn run_jobs(jobs: &[Job], workers: usize) -> Vec<JobResult> {
+    let next_job = AtomicUsize::new(0);
+    let results = Mutex::new(vec![None; jobs.len()]);
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers.clamp(1, jobs.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next_job.fetch_add(1, Ordering::Relaxed);
+                let Some(job) = jobs.get(index) else {
+                    break;
+                };
+                let result = run_job(job);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every job ran"))
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Report {
+    pub results: Vec<JobResult>,
+}
+
+impl Report {
+    pub fn passed(&self) -> usize {
+        self.count(|outcome| matches!(outcome, Outcome::Passed))
+    }
+
+    pub fn failed(&self) -> usize {
+        self.count(|outcome| matches!(outcome, Outcome::Failed(_)))
+    }
+
+    pub fn errors(&self) -> usize {
+        self.count(|outcome| matches!(outcome, Outcome::Error(_)))
+    }
+
+    fn count(&self, filter: fn(&Outcome) -> bool) -> usize {
+        self.results
+            .iter()
+            .filter(|result| filter(&result.outcome))
+            .count()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("reports serialize")
+    }
+
+    pub fn write_junit<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let total: f64 = self
+            .results
+            .iter()
+            .map(|result| result.duration.as_secs_f64())
+            .sum();
+
+        let mut xml = String::new();
+        let _ = writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        let _ = writeln!(
+            xml,
+            r#"<testsuite name="chip8-rs batch" tests="{}" failures="{}" errors="{}" time="{total:.3}">"#,
+            self.results.len(),
+            self.failed(),
+            self.errors()
+        );
+        for result in &self.results {
+            let _ = write!(
+                xml,
+                r#"  <testcase classname="batch" name="{}" time="{:.3}""#,
+                escape_xml(&result.name),
+                result.duration.as_secs_f64()
+            );
+            let _ = match &result.outcome {
+                Outcome::Passed => writeln!(xml, "/>"),
+                Outcome::Failed(message) => writeln!(
+                    xml,
+                    ">\n    <failure message=\"{}\"/>\n  </testcase>",
+                    escape_xml(message)
+                ),
+                Outcome::Error(message) => writeln!(
+                    xml,
+                    ">\n    <error message=\"{}\"/>\n  </testcase>",
+                    escape_xml(message)
+                ),
+            };
+        }
+        let _ = writeln!(xml, "</testsuite>");
+
+        writer.write_all(xml.as_bytes())
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,190 @@
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::batch::*;
+
+    // Clear the screen, then jump to 0x202 forever
+    const LOOP: &[u8] = &[0x00, 0xE0, 0x12, 0x02];
+    // Wait for a key press and release into V0, draw the font sprite for it, then loop
+    const KEY_DRAW: &[u8] = &[0xF0, 0x0A, 0xF0, 0x29, 0xD1, 0x15, 0x12, 0x06];
+
+    const EMPTY_SCREEN: u64 = 0x28c31cf8df2ec325;
+    // The font sprite for 5 in the top left corner
+    const FIVE_DRAWN: u64 = 0x4b4757ec3da1f78b;
+
+    fn job(rom: &[u8], spec: RomSpec) -> Job {
+        Job {
+            name: "test.ch8".to_string(),
+            rom: Ok(rom.to_vec()),
+            spec,
+        }
+    }
+
+    #[test]
+    fn manifest_merges_defaults_into_roms() {
+        let manifest = Manifest::from_toml(
+            r#"
+            cycles = 500
+            quirks = { shift = true }
+
+            [rom."a.ch8"]
+            hash = "28C31CF8DF2EC325"
+            inputs = [
+                { cycle = 20, key = 0x1, pressed = false },
+                { cycle = 10, key = 0x1, pressed = true },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        let rom = manifest.spec("a.ch8");
+        assert_eq!(rom.cycles, 500);
+        assert!(rom.quirks.shift);
+        assert_eq!(rom.expected_hash, Some(EMPTY_SCREEN));
+        assert_eq!(rom.inputs[0].cycle, 10);
+        assert_eq!(manifest.spec("other.ch8"), &manifest.defaults);
+    }
+
+    #[test]
+    fn manifest_rejects_invalid_entries() {
+        for manifest in [
+            "cycle = 10",
+            "quirks = { wrapping = true }",
+            "[rom.\"a.ch8\"]\nhash = \"xyz\"",
+            "[rom.\"a.ch8\"]\ninputs = [{ cycle = 1, key = 16, pressed = true }]",
+        ] {
+            assert!(Manifest::from_toml(manifest).is_err(), "{manifest}");
+        }
+    }
+
+    #[test]
+    fn matching_hash_passes() {
+        let spec = RomSpec {
+            cycles: 100,
+            expected_hash: Some(EMPTY_SCREEN),
+            ..RomSpec::default()
+        };
+        let result = run_job(&job(LOOP, spec));
+        assert_eq!(result.outcome, Outcome::Passed);
+        assert_eq!(result.cycles, 100);
+        assert_eq!(result.hash, EMPTY_SCREEN);
+    }
+
+    #[test]
+    fn inputs_reach_the_rom() {
+        let spec = RomSpec {
+            cycles: 100,
+            expected_hash: Some(FIVE_DRAWN),
+            inputs: vec![
+                Input {
+                    cycle: 10,
+                    key: 0x5,
+                    pressed: true,
+                },
+                Input {
+                    cycle: 20,
+                    key: 0x5,
+                    pressed: false,
+                },
+            ],
+            ..RomSpec::default()
+        };
+        let result = run_job(&job(KEY_DRAW, spec));
+        assert_eq!(result.outcome, Outcome::Passed);
+        assert_eq!(result.hash, FIVE_DRAWN);
+    }
+
+    #[test]
+    fn faults_and_missing_roms_are_errors() {
+        // V0 = 1, then return with an empty call stack
+        let faulting = run_job(&job(&[0x60, 0x01, 0x00, 0xEE], RomSpec::default()));
+        assert!(matches!(faulting.outcome, Outcome::Error(_)));
+        assert_eq!(faulting.cycles, 1);
+
+        let mut missing = job(LOOP, RomSpec::default());
+        missing.rom = Err("could not read ROM".to_string());
+        assert_eq!(
+            run_job(&missing).outcome,
+            Outcome::Error("could not read ROM".to_string())
+        );
+    }
+
+    #[test]
+    fn panics_are_errors_and_other_jobs_still_run() {
+        // Keys are checked when a manifest is loaded, not when a job is built
+        let spec = RomSpec {
+            cycles: 100,
+            inputs: vec![Input {
+                cycle: 10,
+                key: KEYPAD_COUNT,
+                pressed: true,
+            }],
+            ..RomSpec::default()
+        };
+        let jobs = [job(LOOP, spec), job(LOOP, RomSpec::default())];
+
+        let results = run_jobs(&jobs, 2);
+
+        match &results[0].outcome {
+            Outcome::Error(message) => assert!(message.starts_with("panicked: "), "{message}"),
+            outcome => panic!("expected an error, got {outcome:?}"),
+        }
+        assert_eq!(results[0].cycles, 10);
+        assert_eq!(results[1].outcome, Outcome::Passed);
+    }
+
+    #[test]
+    fn results_keep_job_order() {
+        let jobs: Vec<Job> = (0..8)
+            .map(|index| Job {
+                name: format!("{index}.ch8"),
+                ..job(LOOP, RomSpec::default())
+            })
+            .collect();
+        let names: Vec<String> = run_jobs(&jobs, 3)
+            .into_iter()
+            .map(|result| result.name)
+            .collect();
+        let expected: Vec<String> = jobs.into_iter().map(|job| job.name).collect();
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn reports_escape_junit_and_serialize_json() {
+        let report = Report {
+            results: vec![
+                JobResult {
+                    name: "a&b.ch8".to_string(),
+                    outcome: Outcome::Passed,
+                    cycles: 10,
+                    hash: EMPTY_SCREEN,
+                    duration: Duration::from_millis(5),
+                },
+                JobResult {
+                    name: "c.ch8".to_string(),
+                    outcome: Outcome::Failed("<mismatch>".to_string()),
+                    cycles: 10,
+                    hash: 1,
+                    duration: Duration::ZERO,
+                },
+            ],
+        };
+        assert_eq!(
+            (report.passed(), report.failed(), report.errors()),
+            (1, 1, 0)
+        );
+
+        let mut junit = Vec::new();
+        report.write_junit(&mut junit).unwrap();
+        let junit = String::from_utf8(junit).unwrap();
+        assert!(junit.contains(r#"tests="2" failures="1" errors="0""#));
+        assert!(junit.contains(r#"name="a&amp;b.ch8""#));
+        assert!(junit.contains(r#"<failure message="&lt;mismatch&gt;"/>"#));
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["results"][0]["status"], "passed");
+        assert_eq!(json["results"][0]["hash"], "28c31cf8df2ec325");
+        assert_eq!(json["results"][1]["message"], "<mismatch>");
+    }
+}
@@ -0,0 +1,138 @@
+mod tests;
+
+use std::io::Write;
+use std::path::Path;
+
+use crate::emulator::platform::TIMER_FREQUENCY;
+use crate::render::Image;
+
+pub fn write_png<W: Write>(image: &Image, writer: W) -> Result<(), png::EncodingError> {
+    let mut encoder = png::Encoder::new(writer, image.width as u32, image.height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut png_writer = encoder.write_header()?;
+    png_writer.write_image_data(&image.rgba)?;
+    png_writer.finish()
+}
+
+pub fn save_png<P: AsRef<Path>>(image: &Image, path: P) -> Result<(), png::EncodingError> {
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write_png(image, file)
+}
+
+// Records one image per 60 Hz frame, rendered unscaled so that palettes and
+// phosphor decay match screenshots. Repeated frames are merged into a single
+// longer GIF frame
+pub struct GifRecorder {
+    scale: u16,
+    frames: Vec<(Image, u32)>,
+}
+
+impl GifRecorder {
+    pub fn new(scale: u16) -> Self {
+        GifRecorder {
+            scale: scale.max(1),
+            frames: vec![],
+        }
+    }
+
+    pub fn record_frame(&mut self, image: &Image) {
+        match self.frames.last_mut() {
+            Some((last, count)) if last == image => *count += 1,
+            _ => self.frames.push((image.clone(), 1)),
+        }
+    }
+
+    pub fn frame_count(&self) -> u32 {
+        self.frames.iter().map(|(_, count)| count).sum()
+    }
+
+    pub fn write_gif<W: Write>(&self, writer: W) -> Result<(), gif::EncodingError> {
+        let Some((first, _)) = self.frames.first() else {
+            return Ok(());
+        };
+        let scaled = |length: usize| {
+            u16::try_from(length * self.scale as usize).map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("scale {} is too large for a GIF", self.scale),
+                )
+            })
+        };
+        let (width, height) = (scaled(first.width)?, scaled(first.height)?);
+
+        let mut encoder = gif::Encoder::new(writer, width, height, &[])?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+
+        // GIF delays are in hundredths of a second, round the running total so
+        // 60 Hz frames do not drift
+        let mut elapsed_frames = 0;
+        for (image, count) in &self.frames {
+            let start = Self::centiseconds(elapsed_frames);
+            elapsed_frames += count;
+            let end = Self::centiseconds(elapsed_frames);
+
+            let mut frame = self.gif_frame(image, width, height);
+            frame.delay = (end - start) as u16;
+            encoder.write_frame(&frame)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn save_gif<P: AsRef<Path>>(&self, path: P) -> Result<(), gif::EncodingError> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_gif(file)
+    }
+
+    // Colors are exact unless phosphor decay leaves more than 256 shades in a
+    // frame, which are then quantized
+    fn gif_frame(&self, image: &Image, width: u16, height: u16) -> gif::Frame<'static> {
+        let mut colors: Vec<&[u8]> = Vec::new();
+        let mut indices = Vec::with_capacity(image.width * image.height);
+        for pixel in image.rgba.chunks_exact(4) {
+            let color = &pixel[..3];
+            let index = match colors.iter().position(|&known| known == color) {
+                Some(index) => index,
+                None if colors.len() < 256 => {
+                    colors.push(color);
+                    colors.len() - 1
+                }
+                None => {
+                    let mut rgba = self.scale_up(&image.rgba, image.width, 4);
+                    return gif::Frame::from_rgba_speed(width, height, &mut rgba, 10);
+                }
+            };
+            indices.push(index as u8);
+        }
+        gif::Frame::from_palette_pixels(
+            width,
+            height,
+            self.scale_up(&indices, image.width, 1),
+            colors.concat(),
+            None,
+        )
+    }
+
+    fn scale_up(&self, pixels: &[u8], width: usize, bytes_per_pixel: usize) -> Vec<u8> {
+        let scale = self.scale as usize;
+        let mut scaled = Vec::with_capacity(pixels.len() * scale * scale);
+        for row in pixels.chunks(width * bytes_per_pixel) {
+            let start = scaled.len();
+            for pixel in row.chunks(bytes_per_pixel) {
+                for _ in 0..scale {
+                    scaled.extend_from_slice(pixel);
+                }
+            }
+            for _ in 1..scale {
+                scaled.extend_from_within(start..start + row.len() * scale);
+            }
+        }
+        scaled
+    }
+
+    fn centiseconds(frames: u32) -> u32 {
+        (frames * 100 + TIMER_FREQUENCY / 2) / TIMER_FREQUENCY
+    }
+}
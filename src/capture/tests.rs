@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod test {
+    use crate::capture::*;
+    use crate::emulator::framebuffer::Framebuffer;
+    use crate::emulator::platform::headless::HeadlessPlatform;
+    use crate::emulator::platform::{Pixel, PixelState, Platform};
+    use crate::render::{ColorPalette, Renderer};
+
+    fn framebuffer_with_pixel(column: u8, row: u8) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new();
+        framebuffer.set_pixel(Pixel { column, row }, PixelState::On);
+        framebuffer
+    }
+
+    #[test]
+    fn png_keeps_rendered_colors() {
+        let mut renderer = Renderer::new(ColorPalette::xochip()).with_scale(2);
+        let image = renderer.render(&framebuffer_with_pixel(0, 0));
+
+        let mut png_data = vec![];
+        write_png(&image, &mut png_data).unwrap();
+
+        let decoder = png::Decoder::new(std::io::Cursor::new(png_data));
+        let mut reader = decoder.read_info().unwrap();
+        let mut rgba = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut rgba).unwrap();
+
+        assert_eq!((info.width, info.height), (128, 64));
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+        assert_eq!(rgba, image.rgba);
+    }
+
+    fn decode_gif(gif_data: Vec<u8>) -> Vec<gif::Frame<'static>> {
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(std::io::Cursor::new(gif_data)).unwrap();
+        let mut frames = vec![];
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            frames.push(frame.clone());
+        }
+        frames
+    }
+
+    #[test]
+    fn gif_merges_repeated_frames() {
+        let mut renderer = Renderer::new(ColorPalette::default());
+        let mut recorder = GifRecorder::new(1);
+        let first = renderer.render(&framebuffer_with_pixel(0, 0));
+        let second = renderer.render(&framebuffer_with_pixel(5, 5));
+
+        recorder.record_frame(&first);
+        recorder.record_frame(&first);
+        recorder.record_frame(&second);
+
+        let mut gif_data = vec![];
+        recorder.write_gif(&mut gif_data).unwrap();
+        let frames = decode_gif(gif_data);
+
+        assert_eq!(recorder.frame_count(), 3);
+        assert_eq!(frames.len(), 2);
+        // Two and then one 60 Hz frames, 3/60 of a second is 5 hundredths
+        assert_eq!(frames[0].delay + frames[1].delay, 5);
+        assert_eq!(&frames[0].buffer[..], &first.rgba[..]);
+        assert_eq!(&frames[1].buffer[..], &second.rgba[..]);
+    }
+
+    #[test]
+    fn gif_is_scaled_and_keeps_palette_and_phosphor() {
+        let palette = ColorPalette::new(&[[0x10, 0x20, 0x30], [0xa0, 0xb0, 0xc0]]).unwrap();
+        let mut renderer = Renderer::new(palette).with_phosphor_decay(0.5);
+        let mut recorder = GifRecorder::new(2);
+        recorder.record_frame(&renderer.render(&framebuffer_with_pixel(1, 0)));
+        let fading = renderer.render(&Framebuffer::new());
+        recorder.record_frame(&fading);
+
+        let mut gif_data = vec![];
+        recorder.write_gif(&mut gif_data).unwrap();
+        let frames = decode_gif(gif_data);
+
+        assert_eq!((frames[0].width, frames[0].height), (128, 64));
+        // Pixel (1, 0) covers image columns 2 and 3 of rows 0 and 1
+        let color_at = |frame: &gif::Frame, x: usize, y: usize| {
+            let offset = (y * frame.width as usize + x) * 4;
+            [
+                frame.buffer[offset],
+                frame.buffer[offset + 1],
+                frame.buffer[offset + 2],
+            ]
+        };
+        assert_eq!(color_at(&frames[0], 1, 0), [0x10, 0x20, 0x30]);
+        assert_eq!(color_at(&frames[0], 2, 0), [0xa0, 0xb0, 0xc0]);
+        assert_eq!(color_at(&frames[0], 3, 1), [0xa0, 0xb0, 0xc0]);
+        assert_eq!(color_at(&frames[0], 4, 1), [0x10, 0x20, 0x30]);
+        let faded = fading.pixel(1, 0);
+        assert_eq!(color_at(&frames[1], 2, 1), [faded[0], faded[1], faded[2]]);
+        assert_ne!(faded, fading.pixel(0, 0));
+    }
+
+    #[test]
+    fn gif_quantizes_frames_with_many_shades() {
+        let mut rgba = Vec::new();
+        for value in 0..=255u8 {
+            rgba.extend_from_slice(&[value, 0, 0, 0xff, value, 0xff, 0, 0xff]);
+        }
+        let image = Image {
+            width: 64,
+            height: 8,
+            rgba,
+        };
+        let mut recorder = GifRecorder::new(1);
+        recorder.record_frame(&image);
+
+        let mut gif_data = vec![];
+        recorder.write_gif(&mut gif_data).unwrap();
+        let frames = decode_gif(gif_data);
+
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].buffer.len(), image.rgba.len());
+    }
+
+    #[test]
+    fn gif_rejects_scales_past_16_bits() {
+        let mut recorder = GifRecorder::new(1024);
+        recorder.record_frame(&Renderer::new(ColorPalette::default()).render(&Framebuffer::new()));
+
+        assert!(recorder.write_gif(std::io::sink()).is_err());
+    }
+
+    #[tokio::test]
+    async fn capture_reads_pixels_from_platform() {
+        let mut platform = HeadlessPlatform::new(1);
+        let pixel = Pixel {
+            column: 63,
+            row: 31,
+        };
+        platform.set_pixel(pixel, PixelState::On).await;
+
+        let framebuffer = Framebuffer::capture(&platform).await;
+
+        assert_eq!(&framebuffer, platform.framebuffer());
+        assert_eq!(framebuffer.get_pixel(pixel), PixelState::On);
+    }
+}
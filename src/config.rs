@@ -0,0 +1,193 @@
+// User settings from a TOML file, applied over the ROM database preset.
+// Top level settings apply to every ROM, [rom.<sha1>] tables to one ROM:
+//
+//   keymap = "azerty"
+//   tickrate = 15
+//   colors = ["#000", "#0f0"]    # 2, 4 or 16 colors
+//
+//   [keys]
+//   " " = 0x5
+//
+//   [quirks]
+//   shift = true
+//
+//   [gamepad]
+//   up = 0x2
+//
+//   [rom.92a5652d382a18e89c4881ec57041fc7d885ca80]
+//   tickrate = 30
+mod tests;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use derive_more::From;
+use serde::Deserialize;
+
+use crate::emulator::platform::{KeypadNumber, KEYPAD_COUNT};
+use crate::emulator::quirks::Quirks;
+use crate::gamepad::{Control, Profile};
+use crate::keymap::Keymap;
+use crate::render::ColorPalette;
+use crate::rom_database::{parse_color, sha1_hex};
+
+#[derive(Debug, From)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    #[from(skip)]
+    Invalid(String),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(error) => write!(f, "could not read config: {error}"),
+            ConfigError::Toml(error) => write!(f, "invalid config: {error}"),
+            ConfigError::Invalid(message) => write!(f, "invalid config: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSettings {
+    keymap: Option<String>,
+    #[serde(default)]
+    keys: BTreeMap<String, u8>,
+    colors: Option<Vec<String>>,
+    tickrate: Option<u32>,
+    #[serde(default)]
+    quirks: BTreeMap<String, bool>,
+    #[serde(default)]
+    gamepad: BTreeMap<String, u8>,
+}
+
+// Unset fields leave the ROM database preset alone
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Settings {
+    pub keymap: Option<Keymap>,
+    pub palette: Option<ColorPalette>,
+    pub tickrate: Option<u32>,
+    // chip-8-database quirk names
+    pub quirks: BTreeMap<String, bool>,
+    pub gamepad: Option<Profile>,
+}
+
+impl Settings {
+    pub fn apply_quirks(&self, quirks: &mut Quirks) {
+        for (name, value) in &self.quirks {
+            if let Some(quirk) = quirks.by_name_mut(name) {
+                *quirk = *value;
+            }
+        }
+    }
+
+    // Fields set in `other` win, key bindings are added to the keymap
+    fn merged(&self, other: &RawSettings) -> Result<Settings, ConfigError> {
+        let mut keymap = match &other.keymap {
+            Some(preset) => Some(
+                Keymap::preset(preset)
+                    .ok_or_else(|| ConfigError::Invalid(format!("unknown keymap {preset}")))?,
+            ),
+            None => self.keymap.clone(),
+        };
+        for (key, &number) in &other.keys {
+            let mut chars = key.chars();
+            let (Some(key), None) = (chars.next(), chars.next()) else {
+                return Err(ConfigError::Invalid(format!("{key:?} is not a single key")));
+            };
+            keymap
+                .get_or_insert_with(Keymap::default)
+                .bind(key, keypad_key(number)?);
+        }
+
+        let mut gamepad = self.gamepad.clone();
+        for (name, &number) in &other.gamepad {
+            let control = Control::from_name(name)
+                .ok_or_else(|| ConfigError::Invalid(format!("unknown gamepad control {name}")))?;
+            gamepad
+                .get_or_insert_with(Profile::default)
+                .bind(control, keypad_key(number)?);
+        }
+
+        let palette = match &other.colors {
+            Some(colors) => {
+                let colors = colors
+                    .iter()
+                    .map(|color| {
+                        parse_color(color)
+                            .ok_or_else(|| ConfigError::Invalid(format!("invalid color {color}")))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Some(ColorPalette::new(&colors).ok_or_else(|| {
+                    ConfigError::Invalid(format!(
+                        "expected 2, 4 or 16 colors, not {}",
+                        colors.len()
+                    ))
+                })?)
+            }
+            None => self.palette.clone(),
+        };
+
+        let mut quirks = self.quirks.clone();
+        for (name, &value) in &other.quirks {
+            if Quirks::default().by_name_mut(name).is_none() {
+                return Err(ConfigError::Invalid(format!("unknown quirk {name}")));
+            }
+            quirks.insert(name.clone(), value);
+        }
+
+        Ok(Settings {
+            keymap,
+            palette,
+            tickrate: other.tickrate.or(self.tickrate),
+            quirks,
+            gamepad,
+        })
+    }
+}
+
+fn keypad_key(number: u8) -> Result<KeypadNumber, ConfigError> {
+    if number < KEYPAD_COUNT {
+        Ok(KeypadNumber(number))
+    } else {
+        Err(ConfigError::Invalid(format!(
+            "{number} is not a keypad key"
+        )))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Config {
+    pub settings: Settings,
+    // By lowercase SHA-1 of the ROM, already merged with `settings`
+    pub roms: BTreeMap<String, Settings>,
+}
+
+impl Config {
+    pub fn from_toml(text: &str) -> Result<Self, ConfigError> {
+        // Split off [rom.*] first, so that unknown top level keys are rejected
+        let mut table: toml::Table = toml::from_str(text)?;
+        let raw_roms: BTreeMap<String, RawSettings> = match table.remove("rom") {
+            Some(roms) => roms.try_into()?,
+            None => BTreeMap::new(),
+        };
+        let settings = Settings::default().merged(&table.try_into()?)?;
+        let roms = raw_roms
+            .iter()
+            .map(|(hash, rom)| Ok((hash.to_ascii_lowercase(), settings.merged(rom)?)))
+            .collect::<Result<_, ConfigError>>()?;
+        Ok(Config { settings, roms })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn for_rom(&self, rom: &[u8]) -> &Settings {
+        self.roms.get(&sha1_hex(rom)).unwrap_or(&self.settings)
+    }
+}
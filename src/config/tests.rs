@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod test {
+    use crate::config::*;
+
+    // Jump to 0x200 forever
+    const ROM: &[u8] = &[0x12, 0x00];
+
+    const CONFIG: &str = r##"
+        keymap = "azerty"
+        tickrate = 15
+        colors = ["#000", "#00ff00"]
+
+        [keys]
+        " " = 0x5
+
+        [quirks]
+        shift = true
+
+        [gamepad]
+        up = 0x2
+
+        [rom.92A5652D382A18E89C4881EC57041FC7D885CA80]
+        keymap = "dvorak"
+        tickrate = 30
+        colors = ["#000", "#f00", "#0f0", "#00f"]
+
+        [rom.92A5652D382A18E89C4881EC57041FC7D885CA80.quirks]
+        logic = true
+    "##;
+
+    #[test]
+    fn global_settings_apply_to_unknown_roms() {
+        let config = Config::from_toml(CONFIG).unwrap();
+        let settings = config.for_rom(&[0x00, 0xE0]);
+
+        let keymap = settings.keymap.as_ref().unwrap();
+        assert_eq!(keymap.keypad_number('a'), Some(KeypadNumber(0x4)));
+        assert_eq!(keymap.keypad_number(' '), Some(KeypadNumber(0x5)));
+        assert_eq!(settings.tickrate, Some(15));
+        assert_eq!(
+            settings.palette,
+            Some(ColorPalette::monochrome([0, 0, 0], [0, 0xff, 0]))
+        );
+
+        let gamepad = settings.gamepad.as_ref().unwrap();
+        assert_eq!(gamepad.keypad_number(Control::Up), Some(KeypadNumber(0x2)));
+        assert_eq!(
+            gamepad.keypad_number(Control::Down),
+            Some(KeypadNumber(0x8))
+        );
+
+        let mut quirks = Quirks::default();
+        settings.apply_quirks(&mut quirks);
+        assert_eq!(
+            quirks,
+            Quirks {
+                shift: true,
+                ..Quirks::default()
+            }
+        );
+    }
+
+    #[test]
+    fn rom_settings_override_global_settings() {
+        let config = Config::from_toml(CONFIG).unwrap();
+        let settings = config.for_rom(ROM);
+
+        let keymap = settings.keymap.as_ref().unwrap();
+        assert_eq!(keymap.keypad_number('o'), Some(KeypadNumber(0x8)));
+        assert_eq!(keymap.keypad_number(' '), None);
+        assert_eq!(settings.tickrate, Some(30));
+        assert_eq!(
+            settings.palette.as_ref().map(ColorPalette::colors),
+            Some(&[[0, 0, 0], [0xff, 0, 0], [0, 0xff, 0], [0, 0, 0xff]][..])
+        );
+
+        let mut quirks = Quirks::default();
+        settings.apply_quirks(&mut quirks);
+        assert!(quirks.shift && quirks.logic);
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        for config in [
+            "keymap = \"colemak\"",
+            "colors = [\"#000\", \"green\"]",
+            "colors = [\"#000\", \"#111\", \"#222\"]",
+            "tickrate = 10\ntickrat = 20",
+            "[keys]\nqw = 1",
+            "[keys]\nq = 16",
+            "[quirks]\nclip = true",
+            "[gamepad]\nturbo = 1",
+            "[rom.abc]\nspeed = 3",
+        ] {
+            assert!(Config::from_toml(config).is_err(), "{config}");
+        }
+    }
+}
@@ -1,20 +1,85 @@
+pub mod bus;
+pub mod call_stack;
+pub mod cpu_state;
+pub mod framebuffer;
+pub mod instruction;
+pub mod observer;
 pub mod platform;
-
-mod instruction;
+pub mod quirks;
+pub mod types;
+
+mod alu;
+mod decode_cache;
+mod font;
+mod property_tests;
+mod sprite;
 mod tests;
-mod types;
 
 use self::{
-    instruction::Instruction,
-    types::{EightBitValue, MemoryAddress, RegisterNumber},
+    instruction::{table::TableDecoder, Decoder, Instruction},
+    types::{EightBitValue, RegisterNumber},
 };
 
+use bus::{Access, Bus, BusFault, MemoryBus, STANDARD_MEMORY_SIZE};
+use call_stack::{CallStack, StackDepth, StackFault};
+use cpu_state::{CpuState, CpuStateEditor};
+use decode_cache::DecodeCache;
+use derive_more::From;
+use observer::Observer;
 use platform::*;
-pub struct Emulator<PLATFORM: Platform> {
+use quirks::Quirks;
+
+#[cfg(feature = "decode-cache")]
+pub use decode_cache::{CodeModification, MAX_CODE_MODIFICATIONS};
+
+// Stops execution, address is where the faulting instruction was fetched from
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Fault {
+    pub address: u16,
+    pub kind: FaultKind,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, From)]
+pub enum FaultKind {
+    Stack(StackFault),
+    Bus(BusFault),
+    #[from(skip)]
+    InvalidOpcode {
+        opcode: u16,
+    },
+}
+
+impl core::fmt::Display for Fault {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.kind {
+            FaultKind::Stack(fault) => write!(f, "{fault}")?,
+            FaultKind::Bus(fault) => write!(f, "{fault}")?,
+            FaultKind::InvalidOpcode { opcode } => write!(f, "invalid opcode {opcode:04X}")?,
+        }
+        write!(f, " (instruction at {:#05X})", self.address)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Fault {}
+
+// DXYN under the vblank quirk stalls until the next frame and then draws
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum DisplayWait {
+    Idle,
+    Waiting,
+    Ready,
+}
+
+pub struct Emulator<PLATFORM: Platform, BUS: Bus = MemoryBus, OBSERVER: Observer = ()> {
     // Internal State
     program_counter: u16,
-    stack_pointer: usize,
-    stack: Stack,
+    call_stack: CallStack,
+    decode_cache: DecodeCache,
+    waiting_for_key: bool,
+    awaiting_key_release: Option<KeypadNumber>,
+    display_wait: DisplayWait,
+    buzzer: BuzzerState,
 
     // Program Accessible
     i_register: u16,
@@ -23,106 +88,328 @@ pub struct Emulator<PLATFORM: Platform> {
     delay_timer: u8,
 
     // Memory
-    memory: Memory,
+    bus: BUS,
 
     // Platform support
     platform: PLATFORM,
+    observer: OBSERVER,
+
+    // Configuration
+    quirks: Quirks,
+    instructions_per_frame: u32,
+    // Instructions run by run_cycles since the last timer tick
+    frame_cycle: u32,
+    decoder: &'static dyn Decoder,
 }
 
-const MEMORY_SIZE: usize = 4096;
-type Memory = [u8; MEMORY_SIZE];
-
 const REGISTER_BANK_SIZE: usize = 16;
 type RegisterBank = [u8; REGISTER_BANK_SIZE];
 
-const STACK_SIZE: usize = 16;
-type Stack = [u16; STACK_SIZE];
-
 const DATA_START_ADDRESS: u16 = 0x200;
+pub const MAX_ROM_SIZE: usize = STANDARD_MEMORY_SIZE - DATA_START_ADDRESS as usize;
+
+const INSTRUCTION_FREQUENCY: u32 = 500;
+pub const DEFAULT_INSTRUCTIONS_PER_FRAME: u32 = INSTRUCTION_FREQUENCY / TIMER_FREQUENCY;
 
 impl<PLATFORM: Platform> Emulator<PLATFORM> {
     pub fn new(platform: PLATFORM) -> Self {
+        Emulator::with_bus(platform, MemoryBus::standard())
+    }
+}
+
+impl<PLATFORM: Platform, BUS: Bus> Emulator<PLATFORM, BUS> {
+    pub fn with_bus(platform: PLATFORM, mut bus: BUS) -> Self {
         let program_counter = DATA_START_ADDRESS;
-        let stack_pointer = 0;
         let i_register = 0;
         let sound_timer = 0;
         let delay_timer = 0;
-        let memory: Memory = [0; MEMORY_SIZE];
+        bus.load(font::FONT_ADDRESS, &font::FONT)
+            .expect("memory is large enough for the font");
         let v_registers: RegisterBank = [0; REGISTER_BANK_SIZE];
-        let stack: Stack = [0; STACK_SIZE];
 
         Emulator {
             program_counter,
-            stack_pointer,
             i_register,
             sound_timer,
             delay_timer,
             platform,
             v_registers,
-            memory,
-            stack,
+            bus,
+            call_stack: CallStack::default(),
+            decode_cache: DecodeCache::new(),
+            waiting_for_key: false,
+            awaiting_key_release: None,
+            display_wait: DisplayWait::Idle,
+            buzzer: BuzzerState::Off,
+            observer: (),
+            quirks: Quirks::default(),
+            instructions_per_frame: DEFAULT_INSTRUCTIONS_PER_FRAME,
+            frame_cycle: 0,
+            decoder: &TableDecoder,
         }
     }
+}
+
+impl<PLATFORM: Platform, BUS: Bus, OBSERVER: Observer> Emulator<PLATFORM, BUS, OBSERVER> {
+    pub fn with_observer<NEW: Observer>(self, observer: NEW) -> Emulator<PLATFORM, BUS, NEW> {
+        Emulator {
+            program_counter: self.program_counter,
+            call_stack: self.call_stack,
+            decode_cache: self.decode_cache,
+            waiting_for_key: self.waiting_for_key,
+            awaiting_key_release: self.awaiting_key_release,
+            display_wait: self.display_wait,
+            buzzer: self.buzzer,
+            i_register: self.i_register,
+            v_registers: self.v_registers,
+            sound_timer: self.sound_timer,
+            delay_timer: self.delay_timer,
+            bus: self.bus,
+            platform: self.platform,
+            observer,
+            quirks: self.quirks,
+            instructions_per_frame: self.instructions_per_frame,
+            frame_cycle: self.frame_cycle,
+            decoder: self.decoder,
+        }
+    }
+
+    pub fn observer(&self) -> &OBSERVER {
+        &self.observer
+    }
+
+    pub fn observer_mut(&mut self) -> &mut OBSERVER {
+        &mut self.observer
+    }
+
+    pub fn quirks(&self) -> Quirks {
+        self.quirks
+    }
+
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+    }
+
+    pub fn instructions_per_frame(&self) -> u32 {
+        self.instructions_per_frame
+    }
+
+    pub fn set_instructions_per_frame(&mut self, instructions_per_frame: u32) {
+        self.instructions_per_frame = instructions_per_frame;
+    }
+
+    pub fn decoder(&self) -> &'static dyn Decoder {
+        self.decoder
+    }
+
+    // Already decoded instructions are discarded so that every opcode goes through the new decoder
+    pub fn set_decoder(&mut self, decoder: &'static dyn Decoder) {
+        self.decoder = decoder;
+        self.decode_cache.clear();
+    }
+
+    pub fn cpu_state(&self) -> CpuState {
+        CpuState {
+            program_counter: self.program_counter,
+            i_register: self.i_register,
+            v_registers: self.v_registers,
+            stack_pointer: self.call_stack.len(),
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+        }
+    }
+
+    pub fn program_counter(&self) -> u16 {
+        self.program_counter
+    }
+
+    pub fn i_register(&self) -> u16 {
+        self.i_register
+    }
+
+    pub fn registers(&self) -> &[u8; REGISTER_BANK_SIZE] {
+        &self.v_registers
+    }
+
+    // None past VF
+    pub fn register(&self, register: u8) -> Option<u8> {
+        self.v_registers.get(register as usize).copied()
+    }
+
+    pub fn delay_timer(&self) -> u8 {
+        self.delay_timer
+    }
+
+    pub fn sound_timer(&self) -> u8 {
+        self.sound_timer
+    }
+
+    // Fills the buffer from memory starting at address, without access checks
+    pub fn peek_memory(&self, address: u16, buffer: &mut [u8]) -> Result<(), BusFault> {
+        for (offset, value) in buffer.iter_mut().enumerate() {
+            let address = address as usize + offset;
+            *value = u16::try_from(address)
+                .ok()
+                .and_then(|address| self.bus.peek(address))
+                .ok_or(BusFault::OutOfBounds { address })?;
+        }
+        Ok(())
+    }
+
+    pub fn edit(&mut self) -> CpuStateEditor<'_, PLATFORM, BUS, OBSERVER> {
+        CpuStateEditor::new(self)
+    }
+
+    pub fn call_stack(&self) -> &CallStack {
+        &self.call_stack
+    }
+
+    pub fn set_stack_depth(&mut self, depth: StackDepth) -> Result<(), StackFault> {
+        self.call_stack.set_depth(depth)
+    }
+
+    pub fn bus(&self) -> &BUS {
+        &self.bus
+    }
+
+    // Writes through here are not tracked, so decoded instructions are discarded
+    pub fn bus_mut(&mut self) -> &mut BUS {
+        self.decode_cache.clear();
+        &mut self.bus
+    }
+
+    // Executed code the program has since overwritten
+    #[cfg(feature = "decode-cache")]
+    pub fn code_modifications(&self) -> impl Iterator<Item = (u16, CodeModification)> + '_ {
+        self.decode_cache.modifications()
+    }
+
+    // Writes over executed code that code_modifications() had no room for
+    #[cfg(feature = "decode-cache")]
+    pub fn untracked_code_modifications(&self) -> u32 {
+        self.decode_cache.untracked_modifications()
+    }
+
+    pub fn platform(&self) -> &PLATFORM {
+        &self.platform
+    }
+
+    pub fn platform_mut(&mut self) -> &mut PLATFORM {
+        &mut self.platform
+    }
 
-    async fn load_into_memory(&mut self, data: &[u8]) {
-        let destination = &mut self.memory[0..data.len()];
-        // let mut destination = memory[DATA_START_ADDRESS as usize; data.len()];
-        destination.copy_from_slice(&data);
+    pub async fn load_into_memory(&mut self, data: &[u8]) -> Result<(), BusFault> {
+        self.decode_cache.clear();
+        self.bus.load(DATA_START_ADDRESS, data)
     }
 
-    async fn start_program(&mut self) {
-        use futures::select;
-        use futures::FutureExt;
-        let instruction_frequency = core::time::Duration::from_secs(1).div_f32(500f32);
-        let mut instruction_interval = async_io::Timer::interval(instruction_frequency).fuse();
+    pub async fn start_program(&mut self) -> Result<(), Fault> {
+        loop {
+            self.run_frame().await?;
+            self.platform.wait_for_next_frame().await;
+        }
+    }
 
-        let timer_frequency = core::time::Duration::from_secs(1).div_f32(60f32);
-        let mut timer_interval = async_io::Timer::interval(timer_frequency).fuse();
+    pub async fn run_frame(&mut self) -> Result<(), Fault> {
+        for _ in 0..self.instructions_per_frame {
+            self.run_instruction_loop().await?;
+        }
+        self.handle_timers().await;
+        Ok(())
+    }
 
-        select! {
-            _ = timer_interval => self.handle_timers().await,
-            _ = instruction_interval => self.run_instruction_loop().await,
+    // A fixed number of instructions with a timer tick after every
+    // instructions_per_frame of them, later calls carry on mid frame. Returns
+    // how many ran before any fault
+    pub async fn run_cycles(&mut self, cycles: u64) -> (u64, Result<(), Fault>) {
+        for completed in 0..cycles {
+            if let Err(fault) = self.run_instruction_loop().await {
+                return (completed, Err(fault));
+            }
+            self.frame_cycle += 1;
+            if self.frame_cycle >= self.instructions_per_frame {
+                self.frame_cycle = 0;
+                self.handle_timers().await;
+            }
         }
+        (cycles, Ok(()))
     }
 
-    async fn handle_timers(&mut self) {
+    pub async fn handle_timers(&mut self) {
+        // Sampled before the tick, so a sound timer of N beeps for N frames
+        self.handler_buzzer_state().await;
+
         if self.delay_timer > 0 {
             self.delay_timer -= 1;
         }
         if self.sound_timer > 0 {
-            self.delay_timer -= 1;
+            self.sound_timer -= 1;
         }
 
-        self.handler_buzzer_state().await;
+        if self.display_wait == DisplayWait::Waiting {
+            self.display_wait = DisplayWait::Ready;
+        }
     }
 
-    async fn run_instruction_loop(&mut self) {
+    pub async fn run_instruction_loop(&mut self) -> Result<(), Fault> {
+        let result = self.fetch_decode_execute().await;
+        if let Err(fault) = &result {
+            self.observer.on_fault(fault);
+        }
+        result
+    }
+
+    async fn fetch_decode_execute(&mut self) -> Result<(), Fault> {
+        if self.display_wait == DisplayWait::Waiting {
+            return Ok(());
+        }
+
         // Fetch
-        let pc = self.program_counter as usize;
+        let pc = self.program_counter;
+        let fault = |kind: FaultKind| Fault { address: pc, kind };
 
         self.increment_program_counter();
 
-        let instruction_bytes: &[u8; 2] = &self.memory[pc..pc + 2].try_into().unwrap();
-        // Decode
-        let instruction = instruction::parser::parse_instruction(instruction_bytes).unwrap();
+        // Decode, cache hits skip the bus. Program writes invalidate the entries
+        // they overlap and other writes clear the cache, so a hit matches memory
+        let instruction = match self.decode_cache.get(pc) {
+            Some(instruction) => instruction,
+            None => {
+                let instruction_bytes = [
+                    self.bus
+                        .read(pc, Access::Execute)
+                        .map_err(|f| fault(f.into()))?,
+                    self.bus
+                        .read(pc.wrapping_add(1), Access::Execute)
+                        .map_err(|f| fault(f.into()))?,
+                ];
+                let instruction = self.decoder.decode(&instruction_bytes).ok_or(fault(
+                    FaultKind::InvalidOpcode {
+                        opcode: u16::from_be_bytes(instruction_bytes),
+                    },
+                ))?;
+                self.decode_cache.insert(pc, instruction);
+                instruction
+            }
+        };
 
         // Execute
-        self.execute_instruction(instruction).await;
+        self.observer.on_instruction(pc, &instruction);
+        self.execute_instruction(instruction).await.map_err(fault)
     }
 
-    async fn execute_instruction(&mut self, instruction: Instruction) {
+    async fn execute_instruction(&mut self, instruction: Instruction) -> Result<(), FaultKind> {
         match instruction {
             Instruction::System { address } | Instruction::Jump { address } => {
                 self.set_program_counter(address.into());
             }
             Instruction::ClearDisplay => self.platform.clear_display().await,
             Instruction::ReturnFromSubroutine => {
-                self.set_program_counter(self.stack[self.stack_pointer]);
-                self.stack_pointer -= 1;
+                let return_address = self.call_stack.pop()?;
+                self.set_program_counter(return_address);
             }
             Instruction::Call { address } => {
-                self.stack_pointer += 1;
-                self.stack[self.stack_pointer] = self.program_counter;
+                self.call_stack.push(self.program_counter)?;
                 self.set_program_counter(address.into());
             }
             Instruction::SkipNextInstructionIfMatch {
@@ -173,9 +460,12 @@ impl<PLATFORM: Platform> Emulator<PLATFORM> {
                 immediate,
                 destination,
             } => {
-                let value = self.read_v_register(destination) + immediate;
+                // Wraps without touching VF
+                let register_value: u8 = self.read_v_register(destination).into();
+                let immediate_value: u8 = immediate.into();
+                let value = register_value.wrapping_add(immediate_value);
 
-                self.set_v_register(destination, value);
+                self.set_v_register(destination, value.into());
             }
             Instruction::CopyRegisterValue {
                 source,
@@ -189,89 +479,62 @@ impl<PLATFORM: Platform> Emulator<PLATFORM> {
                 source,
                 destination,
             } => {
-                let source_value = self.read_v_register(source);
-                let destination_value = self.read_v_register(source);
-
-                self.set_v_register(destination, destination_value | source_value);
+                self.execute_alu_operation(alu::Operation::Or, source, destination);
             }
             Instruction::BitwiseAndRegisters {
                 source,
                 destination,
             } => {
-                let source_value = self.read_v_register(source);
-                let destination_value = self.read_v_register(source);
-
-                self.set_v_register(destination, destination_value & source_value);
+                self.execute_alu_operation(alu::Operation::And, source, destination);
             }
             Instruction::BitwiseXorRegisters {
                 source,
                 destination,
             } => {
-                let source_value = self.read_v_register(source);
-                let destination_value = self.read_v_register(source);
-
-                self.set_v_register(destination, destination_value ^ source_value);
+                self.execute_alu_operation(alu::Operation::Xor, source, destination);
             }
             Instruction::AddRegisters {
                 source,
                 destination,
             } => {
-                let source_value: u8 = self.read_v_register(source).into();
-                let destination_value: u8 = self.read_v_register(destination).into();
-
-                let (value, carry) = source_value.overflowing_add(destination_value);
-
-                self.set_v_register(destination, value.into());
-                self.set_carry_in_vf_register(carry);
+                self.execute_alu_operation(alu::Operation::Add, source, destination);
             }
-            Instruction::SubtractDestinationFromSource {
+            Instruction::SubtractSourceFromDestination {
                 source,
                 destination,
             } => {
-                let source_value = self.read_v_register(source);
-                let destination_value = self.read_v_register(source);
-
-                self.set_v_register(destination, source_value - destination_value);
+                self.execute_alu_operation(alu::Operation::Subtract, source, destination);
             }
-            Instruction::SubtractSourceFromDestination {
+            Instruction::SubtractDestinationFromSource {
                 source,
                 destination,
             } => {
-                let source_value = self.read_v_register(source);
-                let destination_value = self.read_v_register(source);
-
-                self.set_v_register(destination, destination_value - source_value);
+                self.execute_alu_operation(alu::Operation::SubtractReversed, source, destination);
             }
             Instruction::ShiftRightRegisters {
                 source,
                 destination,
             } => {
-                let source_value: u8 = self.read_v_register(source).into();
-                let destination_value: u8 = self.read_v_register(destination).into();
-
-                let (value, carry) = source_value.overflowing_shr(destination_value.into());
-
-                self.set_v_register(destination, value.into());
-                self.set_carry_in_vf_register(carry);
+                self.execute_alu_operation(alu::Operation::ShiftRight, source, destination);
             }
             Instruction::ShiftLeftRegisters {
                 source,
                 destination,
             } => {
-                let source_value: u8 = self.read_v_register(source).into();
-                let destination_value: u8 = self.read_v_register(destination).into();
-
-                let (value, carry) = source_value.overflowing_shl(destination_value.into());
-
-                self.set_v_register(destination, value.into());
-                self.set_carry_in_vf_register(carry);
+                self.execute_alu_operation(alu::Operation::ShiftLeft, source, destination);
             }
             Instruction::LoadToIRegister { immediate } => {
                 self.i_register = immediate.into();
             }
             Instruction::JumpToSumOfV0ValueAndImmediate { immediate } => {
-                let register_value: u8 = self.read_v_register(RegisterNumber::zero()).into();
                 let immediate_value: u16 = immediate.into();
+                let register = if self.quirks.jump {
+                    // BXNN, the register is the top nibble of the address
+                    RegisterNumber(((immediate_value >> 8) as u8).into())
+                } else {
+                    RegisterNumber::zero()
+                };
+                let register_value: u8 = self.read_v_register(register).into();
 
                 self.set_program_counter(immediate_value + register_value as u16);
             }
@@ -279,7 +542,8 @@ impl<PLATFORM: Platform> Emulator<PLATFORM> {
                 read_key_number_from,
             } => {
                 let register_value: u8 = self.read_v_register(read_key_number_from).into();
-                let expected_key_number = KeypadNumber(register_value.into());
+                // Only the low nibble selects a key, like on the COSMAC VIP
+                let expected_key_number = KeypadNumber(register_value & 0xF);
 
                 if let KeyState::On = self.platform.read_keypress_state(expected_key_number).await {
                     self.increment_program_counter();
@@ -289,7 +553,7 @@ impl<PLATFORM: Platform> Emulator<PLATFORM> {
                 read_key_number_from,
             } => {
                 let register_value: u8 = self.read_v_register(read_key_number_from).into();
-                let expected_key_number = KeypadNumber(register_value.into());
+                let expected_key_number = KeypadNumber(register_value & 0xF);
 
                 if let KeyState::Off = self.platform.read_keypress_state(expected_key_number).await
                 {
@@ -307,42 +571,30 @@ impl<PLATFORM: Platform> Emulator<PLATFORM> {
             }
             Instruction::AddValueToIRegister { source } => {
                 let register_value: u8 = self.read_v_register(source).into();
-                self.i_register += register_value as u16;
+                self.i_register = self.i_register.wrapping_add(register_value as u16);
             }
             Instruction::LoadSpriteLocationForValueIntoIRegister { source } => {
                 let register_value: u8 = self.read_v_register(source).into();
-                if let Some(address) = self.find_address_of_value_in_memory(register_value) {
-                    self.i_register = address.into();
-                }
+                self.i_register = font::character_address(register_value);
             }
             Instruction::LoadValuesFromV0ToRegisterIntoSequenceStartingAtIRegisterValue { end } => {
+                let end_value: u8 = end.0.into();
+                self.invalidate_decoded_instructions(self.i_register, end_value as usize + 1);
                 let register_slice = Self::get_register_slice_up_to(&self.v_registers, end);
-
-                let memory_start_address = MemoryAddress(self.i_register.into());
-                let bytes_to_read: u8 = end.0.into();
-                let memory_slice = Self::get_mut_memory_slice(
-                    &mut self.memory,
-                    memory_start_address,
-                    bytes_to_read,
-                );
-
-                memory_slice.copy_from_slice(register_slice);
+                Self::write_memory(&mut self.bus, self.i_register, register_slice)?;
+                self.advance_i_register_past(end);
             }
             Instruction::LoadSequenceStartingAtIRegisterValueIntoV0ToRegister { end } => {
-                let memory_start_address = MemoryAddress(self.i_register.into());
-                let bytes_to_read: u8 = end.0.into();
-                let memory_slice =
-                    Self::get_memory_slice(&mut self.memory, memory_start_address, bytes_to_read);
-
                 let register_slice = Self::get_mut_register_slice_up_to(&mut self.v_registers, end);
-                register_slice.copy_from_slice(memory_slice);
+                Self::read_memory(&mut self.bus, self.i_register, register_slice)?;
+                self.advance_i_register_past(end);
             }
             Instruction::LoadBitwiseAndOfRandomByteAndImmediate {
                 destination,
                 immediate,
             } => {
                 let immediate_value: u8 = immediate.into();
-                let random_value: u8 = rand::random();
+                let random_value: u8 = self.platform.random_byte().await;
                 let result = immediate_value & random_value;
 
                 self.set_v_register(destination, result.into());
@@ -352,155 +604,143 @@ impl<PLATFORM: Platform> Emulator<PLATFORM> {
                 self.set_v_register(destination, value);
             }
             Instruction::AwaitKeyPressAndLoadIntoRegister { destination } => {
-                let key_state = self.platform.block_for_any_keypress().await;
-                // TODO Timers should still tick, use async or decrement PC and loop again
-
-                match key_state {
-                    KeyState::On => self.set_v_register(destination, 1.into()),
-                    KeyState::Off => self.set_v_register(destination, 0.into()),
+                // Like the COSMAC VIP, keep executing FX0A until a key is pressed and
+                // released so that timers keep ticking while waiting
+                if !self.waiting_for_key {
+                    self.waiting_for_key = true;
+                    self.observer.on_key_wait();
+                }
+                match self.awaiting_key_release {
+                    None => {
+                        self.awaiting_key_release = self.platform.get_pressed_key().await;
+                        self.decrement_program_counter();
+                    }
+                    Some(key) => match self.platform.read_keypress_state(key).await {
+                        KeyState::On => self.decrement_program_counter(),
+                        KeyState::Off => {
+                            self.waiting_for_key = false;
+                            self.awaiting_key_release = None;
+                            self.set_v_register(destination, key.0.into());
+                            self.observer.on_key_wait_end(key);
+                        }
+                    },
                 }
             }
             Instruction::LoadBinaryCodedDecimalValueIntoSequenceStartingAtIRegisterValue {
                 source,
             } => {
-                // Generate digits. Order will be smallest to highest digit
-                let mut digits = vec![];
-                let mut bcd_value: u8 = self.read_v_register(source).into();
-                while bcd_value != 0 {
-                    let digit = bcd_value % 10;
-                    digits.push(digit);
-                    bcd_value /= 10;
-                }
-
-                // Reverse to store in memory
-                digits = digits.into_iter().rev().collect();
+                let value: u8 = self.read_v_register(source).into();
+                let digits = [value / 100, (value / 10) % 10, value % 10];
 
-                let memory_start_address = MemoryAddress(self.i_register.into());
-                let bytes_to_read = digits.len() as u8;
-                let memory_slice = Self::get_mut_memory_slice(
-                    &mut self.memory,
-                    memory_start_address,
-                    bytes_to_read,
-                );
-
-                memory_slice.copy_from_slice(digits.as_slice());
+                self.invalidate_decoded_instructions(self.i_register, digits.len());
+                Self::write_memory(&mut self.bus, self.i_register, &digits)?;
             }
             Instruction::DrawSpritesFromMemory {
                 read_x_axis_from,
                 read_y_axis_from,
                 bytes_to_read_from_i_register,
             } => {
-                let starting_x_value: u8 = self.read_v_register(read_x_axis_from).into();
-                let starting_y_value: u8 = self.read_v_register(read_y_axis_from).into();
-
-                // Wrap Starting position
-                let display_width = self.platform.get_display_width().await;
-                let display_height = self.platform.get_display_width().await;
-                let starting_x_value = starting_x_value % display_width;
-                let starting_y_value = starting_y_value % display_height;
+                if self.quirks.vblank && self.display_wait != DisplayWait::Ready {
+                    self.display_wait = DisplayWait::Waiting;
+                    self.decrement_program_counter();
+                    return Ok(());
+                }
+                self.display_wait = DisplayWait::Idle;
 
-                // Set carry
-                self.v_registers[0xf] = 0;
+                let x: u8 = self.read_v_register(read_x_axis_from).into();
+                let y: u8 = self.read_v_register(read_y_axis_from).into();
 
-                // Fetch sprites
-                let memory_start_address = MemoryAddress(self.i_register.into());
                 let bytes_to_read: u8 = bytes_to_read_from_i_register.into();
-                let sprites =
-                    Self::get_memory_slice(&self.memory, memory_start_address, bytes_to_read);
-
-                use bitvec::prelude::*;
-
-                // Render
-                let mut row = starting_y_value;
-                for sprite in sprites {
-                    let bool_to_pixel_state = |pixel_set| {
-                        if pixel_set {
-                            PixelState::On
-                        } else {
-                            PixelState::Off
-                        }
-                    };
-                    let pixel_iter = sprite
-                        .view_bits::<Msb0>()
-                        .iter()
-                        .by_vals()
-                        .map(bool_to_pixel_state);
-
-                    let mut column = starting_x_value;
-                    for pixel_state in pixel_iter {
-                        let pixel = Pixel {
-                            column: starting_x_value,
-                            row: starting_y_value,
-                        };
-                        let current_state = self.platform.get_pixel(pixel).await;
-
-                        if pixel_state == current_state {
-                            self.platform.set_pixel(pixel, PixelState::Off).await;
-                            self.v_registers[0xf] = 1;
-                        } else {
-                            self.platform.set_pixel(pixel, PixelState::On).await;
-                        }
-                        // Needed?
-                        column += 1;
-                        if column >= display_width {
-                            break;
-                        }
-                    }
+                let mut sprite_buffer = [0; 15];
+                let sprite = &mut sprite_buffer[..bytes_to_read as usize];
+                Self::read_memory(&mut self.bus, self.i_register, sprite)?;
 
-                    row += 1;
-
-                    // Needed?
-                    if row >= display_height {
-                        break;
-                    }
-                }
+                let collision =
+                    sprite::draw(&mut self.platform, x, y, sprite, self.quirks.wrap).await;
+                self.set_carry_in_vf_register(collision);
+                self.observer.on_draw(x, y, bytes_to_read, collision);
             }
         };
+        Ok(())
     }
 
+    // FX55 and FX65 include VX
     fn get_register_slice_up_to(registers: &RegisterBank, end: RegisterNumber) -> &[u8] {
         let register_end_index: usize = end.into();
-        &registers[0..register_end_index]
+        &registers[0..=register_end_index]
     }
     fn get_mut_register_slice_up_to(
         registers: &mut RegisterBank,
         end: RegisterNumber,
     ) -> &mut [u8] {
         let register_end_index: usize = end.into();
-        &mut registers[0..register_end_index]
+        &mut registers[0..=register_end_index]
     }
 
-    fn get_memory_slice(memory: &Memory, start_address: MemoryAddress, bytes: u8) -> &[u8] {
-        let memory_start_address: usize = start_address.into();
-        let memory_end_address = memory_start_address + bytes as usize;
-        &memory[memory_start_address..memory_end_address]
+    fn advance_i_register_past(&mut self, end: RegisterNumber) {
+        let end_value: u8 = end.0.into();
+        if self.quirks.memory_leave_i_unchanged {
+            return;
+        }
+        let increment = if self.quirks.memory_increment_by_x {
+            end_value as u16
+        } else {
+            end_value as u16 + 1
+        };
+        self.i_register = self.i_register.wrapping_add(increment);
     }
-    fn get_mut_memory_slice(
-        memory: &mut Memory,
-        start_address: MemoryAddress,
-        bytes: u8,
-    ) -> &mut [u8] {
-        let memory_start_address: usize = start_address.into();
 
-        let memory_end_address = memory_start_address + bytes as usize;
-        &mut memory[memory_start_address..memory_end_address]
+    fn read_memory(bus: &mut BUS, start_address: u16, buffer: &mut [u8]) -> Result<(), BusFault> {
+        for (offset, value) in buffer.iter_mut().enumerate() {
+            *value = bus.read(start_address.wrapping_add(offset as u16), Access::Read)?;
+        }
+        Ok(())
     }
 
-    fn find_address_of_value_in_memory(&self, value: u8) -> Option<MemoryAddress> {
-        self.memory
-            .iter()
-            .position(|&memory_value| memory_value == value)
-            .and_then(|opt| Some(MemoryAddress((opt as u16).into())))
+    // Called while executing, the program counter is already past the writing instruction
+    fn invalidate_decoded_instructions(&mut self, start_address: u16, length: usize) {
+        let written_by = self.program_counter.wrapping_sub(2);
+        for offset in 0..length {
+            self.decode_cache
+                .invalidate(start_address.wrapping_add(offset as u16), written_by);
+        }
+    }
+
+    fn write_memory(bus: &mut BUS, start_address: u16, values: &[u8]) -> Result<(), BusFault> {
+        for (offset, &value) in values.iter().enumerate() {
+            bus.write(start_address.wrapping_add(offset as u16), value)?;
+        }
+        Ok(())
     }
 
     fn increment_program_counter(&mut self) {
-        self.program_counter += 2;
+        self.program_counter = self.program_counter.wrapping_add(2);
+    }
+
+    fn decrement_program_counter(&mut self) {
+        self.program_counter = self.program_counter.wrapping_sub(2);
     }
 
     fn set_program_counter(&mut self, value: u16) {
         self.program_counter = value;
     }
 
+    fn execute_alu_operation(
+        &mut self,
+        operation: alu::Operation,
+        source: RegisterNumber,
+        destination: RegisterNumber,
+    ) {
+        let x: u8 = self.read_v_register(destination).into();
+        let y: u8 = self.read_v_register(source).into();
+        let output = alu::execute(operation, x, y, &self.quirks);
+
+        self.set_v_register(destination, output.value.into());
+        if let Some(flag) = output.flag {
+            self.set_carry_in_vf_register(flag);
+        }
+    }
+
     fn set_carry_in_vf_register(&mut self, carry_set: bool) {
         self.v_registers[0xf] = carry_set.into();
     }
@@ -517,10 +757,19 @@ impl<PLATFORM: Platform> Emulator<PLATFORM> {
     }
 
     async fn handler_buzzer_state(&mut self) {
-        if self.sound_timer > 0 {
-            self.platform.set_buzzer(BuzzerState::On).await;
+        let buzzer = if self.sound_timer > 0 {
+            BuzzerState::On
         } else {
-            self.platform.set_buzzer(BuzzerState::Off).await;
+            BuzzerState::Off
+        };
+        self.platform.set_buzzer(buzzer).await;
+
+        if buzzer != self.buzzer {
+            self.buzzer = buzzer;
+            match buzzer {
+                BuzzerState::On => self.observer.on_sound_start(),
+                BuzzerState::Off => self.observer.on_sound_stop(),
+            }
         }
     }
 }
@@ -0,0 +1,76 @@
+mod tests;
+
+use super::quirks::Quirks;
+
+// 8XY1 to 8XYE, X is the destination and Y the source
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Operation {
+    Or,
+    And,
+    Xor,
+    Add,
+    // VX - VY
+    Subtract,
+    // VY - VX
+    SubtractReversed,
+    ShiftRight,
+    ShiftLeft,
+}
+
+// The flag must be written to VF after the value is written to VX, so that the
+// flag wins when VF is the destination
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Output {
+    pub value: u8,
+    pub flag: Option<bool>,
+}
+
+pub fn execute(operation: Operation, x: u8, y: u8, quirks: &Quirks) -> Output {
+    let logic_flag = quirks.logic.then_some(false);
+    // Without the shift quirk VY is shifted into VX
+    let shift_operand = if quirks.shift { x } else { y };
+
+    match operation {
+        Operation::Or => Output {
+            value: x | y,
+            flag: logic_flag,
+        },
+        Operation::And => Output {
+            value: x & y,
+            flag: logic_flag,
+        },
+        Operation::Xor => Output {
+            value: x ^ y,
+            flag: logic_flag,
+        },
+        Operation::Add => {
+            let (value, carry) = x.overflowing_add(y);
+            Output {
+                value,
+                flag: Some(carry),
+            }
+        }
+        Operation::Subtract => {
+            let (value, borrow) = x.overflowing_sub(y);
+            Output {
+                value,
+                flag: Some(!borrow),
+            }
+        }
+        Operation::SubtractReversed => {
+            let (value, borrow) = y.overflowing_sub(x);
+            Output {
+                value,
+                flag: Some(!borrow),
+            }
+        }
+        Operation::ShiftRight => Output {
+            value: shift_operand >> 1,
+            flag: Some(shift_operand & 0x01 != 0),
+        },
+        Operation::ShiftLeft => Output {
+            value: shift_operand << 1,
+            flag: Some(shift_operand & 0x80 != 0),
+        },
+    }
+}
@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod test {
+    use crate::emulator::alu::*;
+
+    // (VX, VY, expected VX, expected VF)
+    type Case = (u8, u8, u8, Option<bool>);
+
+    fn check(operation: Operation, quirks: Quirks, cases: &[Case]) {
+        for &(x, y, value, flag) in cases {
+            let output = execute(operation, x, y, &quirks);
+            assert_eq!(
+                output,
+                Output { value, flag },
+                "{operation:?} with VX = {x:#04x}, VY = {y:#04x}"
+            );
+        }
+    }
+
+    // OpCode 8XY1
+    #[test]
+    fn or() {
+        let cases = [(0x0F, 0xF0, 0xFF, None), (0x55, 0x00, 0x55, None)];
+        check(Operation::Or, Quirks::default(), &cases);
+
+        let cases = [(0x0F, 0xF0, 0xFF, Some(false))];
+        check(Operation::Or, Quirks::cosmac_vip(), &cases);
+    }
+
+    // OpCode 8XY2
+    #[test]
+    fn and() {
+        let cases = [(0x0F, 0xF0, 0x00, None), (0x3C, 0x0F, 0x0C, None)];
+        check(Operation::And, Quirks::default(), &cases);
+
+        let cases = [(0x3C, 0x0F, 0x0C, Some(false))];
+        check(Operation::And, Quirks::cosmac_vip(), &cases);
+    }
+
+    // OpCode 8XY3
+    #[test]
+    fn xor() {
+        let cases = [(0x0F, 0xFF, 0xF0, None), (0xAA, 0xAA, 0x00, None)];
+        check(Operation::Xor, Quirks::default(), &cases);
+
+        let cases = [(0x0F, 0xFF, 0xF0, Some(false))];
+        check(Operation::Xor, Quirks::cosmac_vip(), &cases);
+    }
+
+    // OpCode 8XY4
+    #[test]
+    fn add() {
+        let cases = [
+            (0x05, 0x0A, 0x0F, Some(false)),
+            (0xFF, 0x01, 0x00, Some(true)),
+            (0xFF, 0xFF, 0xFE, Some(true)),
+            (0x80, 0x7F, 0xFF, Some(false)),
+        ];
+        check(Operation::Add, Quirks::default(), &cases);
+    }
+
+    // OpCode 8XY5
+    #[test]
+    fn subtract() {
+        let cases = [
+            (0x0A, 0x05, 0x05, Some(true)),
+            (0x05, 0x0A, 0xFB, Some(false)),
+            (0x05, 0x05, 0x00, Some(true)),
+            (0x00, 0xFF, 0x01, Some(false)),
+        ];
+        check(Operation::Subtract, Quirks::default(), &cases);
+    }
+
+    // OpCode 8XY6
+    #[test]
+    fn shift_right() {
+        let cases = [
+            (0x00, 0x05, 0x02, Some(true)),
+            (0xFF, 0x04, 0x02, Some(false)),
+            (0x00, 0x01, 0x00, Some(true)),
+        ];
+        check(Operation::ShiftRight, Quirks::default(), &cases);
+
+        let cases = [
+            (0x05, 0x00, 0x02, Some(true)),
+            (0x04, 0xFF, 0x02, Some(false)),
+        ];
+        check(Operation::ShiftRight, Quirks::superchip(), &cases);
+    }
+
+    // OpCode 8XY7
+    #[test]
+    fn subtract_reversed() {
+        let cases = [
+            (0x05, 0x0A, 0x05, Some(true)),
+            (0x0A, 0x05, 0xFB, Some(false)),
+            (0x05, 0x05, 0x00, Some(true)),
+            (0xFF, 0x00, 0x01, Some(false)),
+        ];
+        check(Operation::SubtractReversed, Quirks::default(), &cases);
+    }
+
+    // OpCode 8XYE
+    #[test]
+    fn shift_left() {
+        let cases = [
+            (0x00, 0x81, 0x02, Some(true)),
+            (0xFF, 0x40, 0x80, Some(false)),
+            (0x00, 0x80, 0x00, Some(true)),
+        ];
+        check(Operation::ShiftLeft, Quirks::default(), &cases);
+
+        let cases = [
+            (0x81, 0x00, 0x02, Some(true)),
+            (0x40, 0xFF, 0x80, Some(false)),
+        ];
+        check(Operation::ShiftLeft, Quirks::superchip(), &cases);
+    }
+}
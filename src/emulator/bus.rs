@@ -0,0 +1,218 @@
+// Everything the CPU reads or writes goes through a Bus, so memory layouts and
+// instrumentation can be swapped in without touching instruction execution.
+mod tests;
+
+use core::fmt;
+use core::ops::Range;
+
+pub const STANDARD_MEMORY_SIZE: usize = 0x1000;
+// XO-CHIP, the whole 16 bit address space
+pub const EXTENDED_MEMORY_SIZE: usize = 0x10000;
+// Where the interpreter and font live on the COSMAC VIP
+pub const INTERPRETER_AREA: Range<usize> = 0x000..0x200;
+pub const MAX_REGIONS: usize = 8;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Access {
+    Read,
+    Write,
+    Execute,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RegionKind {
+    Rom,
+    Ram,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Region {
+    pub range: Range<usize>,
+    pub kind: RegionKind,
+}
+
+const UNUSED_REGION: Region = Region {
+    range: 0..0,
+    kind: RegionKind::Ram,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BusFault {
+    OutOfBounds { address: usize },
+    ReadOnly { address: u16 },
+}
+
+impl fmt::Display for BusFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BusFault::OutOfBounds { address } => {
+                write!(f, "address {address:#05X} is outside memory")
+            }
+            BusFault::ReadOnly { address } => {
+                write!(f, "write to read-only address {address:#05X}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BusFault {}
+
+pub trait Bus {
+    fn size(&self) -> usize;
+
+    // Reads without access checks or hooks, for debuggers and tests
+    fn peek(&self, address: u16) -> Option<u8>;
+
+    fn read(&mut self, address: u16, access: Access) -> Result<u8, BusFault>;
+
+    fn write(&mut self, address: u16, value: u8) -> Result<(), BusFault>;
+
+    // Copies in data regardless of region attributes, used for the font and ROMs
+    fn load(&mut self, address: u16, data: &[u8]) -> Result<(), BusFault>;
+}
+
+// Called after every successful access through a MemoryBus. Instructions the
+// emulator has already decoded are not fetched again until they are overwritten
+pub trait BusHook {
+    fn on_access(&mut self, address: u16, value: u8, access: Access);
+}
+
+impl BusHook for () {
+    fn on_access(&mut self, _address: u16, _value: u8, _access: Access) {}
+}
+
+// Memory is stored inline, so no allocator is needed
+pub struct MemoryBus<const SIZE: usize = STANDARD_MEMORY_SIZE, HOOK: BusHook = ()> {
+    memory: [u8; SIZE],
+    // Later regions take priority, unmapped memory is RAM
+    regions: [Region; MAX_REGIONS],
+    region_count: usize,
+    hook: HOOK,
+}
+
+impl MemoryBus {
+    // The COSMAC VIP layout, a read-only interpreter area followed by program RAM
+    pub fn standard() -> Self {
+        MemoryBus::new().with_region(INTERPRETER_AREA, RegionKind::Rom)
+    }
+}
+
+impl MemoryBus<EXTENDED_MEMORY_SIZE> {
+    pub fn extended() -> Self {
+        MemoryBus::new().with_region(INTERPRETER_AREA, RegionKind::Rom)
+    }
+}
+
+impl<const SIZE: usize> MemoryBus<SIZE> {
+    pub fn new() -> Self {
+        const {
+            assert!(
+                SIZE <= EXTENDED_MEMORY_SIZE,
+                "memory is addressed with 16 bits"
+            )
+        };
+        MemoryBus {
+            memory: [0; SIZE],
+            regions: [UNUSED_REGION; MAX_REGIONS],
+            region_count: 0,
+            hook: (),
+        }
+    }
+}
+
+impl<const SIZE: usize, HOOK: BusHook> MemoryBus<SIZE, HOOK> {
+    pub fn with_region(mut self, range: Range<usize>, kind: RegionKind) -> Self {
+        assert!(
+            self.region_count < MAX_REGIONS,
+            "at most {MAX_REGIONS} regions"
+        );
+        self.regions[self.region_count] = Region { range, kind };
+        self.region_count += 1;
+        self
+    }
+
+    pub fn with_hook<NEW: BusHook>(self, hook: NEW) -> MemoryBus<SIZE, NEW> {
+        MemoryBus {
+            memory: self.memory,
+            regions: self.regions,
+            region_count: self.region_count,
+            hook,
+        }
+    }
+
+    pub fn hook(&self) -> &HOOK {
+        &self.hook
+    }
+
+    pub fn hook_mut(&mut self) -> &mut HOOK {
+        &mut self.hook
+    }
+
+    pub fn regions(&self) -> &[Region] {
+        &self.regions[..self.region_count]
+    }
+
+    pub fn region_kind(&self, address: u16) -> RegionKind {
+        self.regions()
+            .iter()
+            .rev()
+            .find(|region| region.range.contains(&(address as usize)))
+            .map_or(RegionKind::Ram, |region| region.kind)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.memory
+    }
+
+    fn index(&self, address: u16) -> Result<usize, BusFault> {
+        let index = address as usize;
+        if index < self.memory.len() {
+            Ok(index)
+        } else {
+            Err(BusFault::OutOfBounds { address: index })
+        }
+    }
+}
+
+impl<const SIZE: usize, HOOK: BusHook> Bus for MemoryBus<SIZE, HOOK> {
+    fn size(&self) -> usize {
+        self.memory.len()
+    }
+
+    fn peek(&self, address: u16) -> Option<u8> {
+        self.memory.get(address as usize).copied()
+    }
+
+    fn read(&mut self, address: u16, access: Access) -> Result<u8, BusFault> {
+        let value = self.memory[self.index(address)?];
+        self.hook.on_access(address, value, access);
+        Ok(value)
+    }
+
+    fn write(&mut self, address: u16, value: u8) -> Result<(), BusFault> {
+        let index = self.index(address)?;
+        if self.region_kind(address) == RegionKind::Rom {
+            return Err(BusFault::ReadOnly { address });
+        }
+        self.memory[index] = value;
+        self.hook.on_access(address, value, Access::Write);
+        Ok(())
+    }
+
+    fn load(&mut self, address: u16, data: &[u8]) -> Result<(), BusFault> {
+        let start = address as usize;
+        let end = start + data.len();
+        if end > self.memory.len() {
+            return Err(BusFault::OutOfBounds { address: end - 1 });
+        }
+        self.memory[start..end].copy_from_slice(data);
+        Ok(())
+    }
+}
+
+impl Default for MemoryBus {
+    fn default() -> Self {
+        MemoryBus::standard()
+    }
+}
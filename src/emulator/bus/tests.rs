@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod test {
+    use crate::emulator::bus::*;
+
+    #[derive(Default)]
+    struct AccessLog(Vec<(u16, u8, Access)>);
+
+    impl BusHook for AccessLog {
+        fn on_access(&mut self, address: u16, value: u8, access: Access) {
+            self.0.push((address, value, access));
+        }
+    }
+
+    #[test]
+    fn interpreter_area_is_read_only() {
+        let mut bus = MemoryBus::standard();
+
+        assert_eq!(
+            bus.write(0x1FF, 0xAB),
+            Err(BusFault::ReadOnly { address: 0x1FF })
+        );
+        assert_eq!(bus.write(0x200, 0xAB), Ok(()));
+        assert_eq!(bus.peek(0x1FF), Some(0));
+        assert_eq!(bus.peek(0x200), Some(0xAB));
+    }
+
+    #[test]
+    fn load_ignores_region_kind() {
+        let mut bus = MemoryBus::standard();
+
+        bus.load(0x050, &[1, 2, 3]).unwrap();
+
+        assert_eq!(&bus.as_bytes()[0x050..0x053], [1, 2, 3]);
+    }
+
+    #[test]
+    fn access_past_end_of_memory_faults() {
+        let mut bus = MemoryBus::standard();
+
+        assert_eq!(
+            bus.read(0x1000, Access::Read),
+            Err(BusFault::OutOfBounds { address: 0x1000 })
+        );
+        assert_eq!(
+            bus.load(0xFFE, &[0; 4]),
+            Err(BusFault::OutOfBounds { address: 0x1001 })
+        );
+    }
+
+    #[test]
+    fn extended_memory_covers_address_space() {
+        let mut bus = MemoryBus::extended();
+
+        bus.write(0xFFFF, 0x12).unwrap();
+
+        assert_eq!(bus.size(), 0x10000);
+        assert_eq!(bus.read(0xFFFF, Access::Read), Ok(0x12));
+    }
+
+    #[test]
+    fn later_regions_take_priority() {
+        let bus = MemoryBus::<0x100>::new()
+            .with_region(0x00..0x80, RegionKind::Rom)
+            .with_region(0x40..0x50, RegionKind::Ram);
+
+        assert_eq!(bus.region_kind(0x3F), RegionKind::Rom);
+        assert_eq!(bus.region_kind(0x40), RegionKind::Ram);
+        assert_eq!(bus.region_kind(0x50), RegionKind::Rom);
+        assert_eq!(bus.region_kind(0x80), RegionKind::Ram);
+    }
+
+    #[test]
+    fn hook_sees_successful_accesses() {
+        let mut bus = MemoryBus::standard().with_hook(AccessLog::default());
+
+        bus.write(0x300, 7).unwrap();
+        bus.read(0x300, Access::Read).unwrap();
+        bus.read(0x200, Access::Execute).unwrap();
+        bus.write(0x000, 1).unwrap_err();
+
+        assert_eq!(
+            bus.hook().0,
+            [
+                (0x300, 7, Access::Write),
+                (0x300, 7, Access::Read),
+                (0x200, 0, Access::Execute),
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "at most 8 regions")]
+    fn regions_are_limited() {
+        let mut bus = MemoryBus::<0x100>::new();
+        for start in 0..=MAX_REGIONS {
+            bus = bus.with_region(start..start + 1, RegionKind::Rom);
+        }
+    }
+}
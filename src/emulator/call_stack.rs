@@ -0,0 +1,165 @@
+// Return addresses pushed by 2NNN and popped by 00EE. Interpreters disagree on
+// how deep calls can nest, so the depth is configurable.
+mod tests;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt;
+
+// Limited stacks are stored inline, so this is the deepest one can be
+pub const MAX_STACK_DEPTH: usize = 64;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StackDepth {
+    // At most MAX_STACK_DEPTH
+    Limited(usize),
+    // Only limited by memory, useful when debugging runaway recursion
+    #[cfg(feature = "alloc")]
+    Unbounded,
+}
+
+impl StackDepth {
+    pub const COSMAC_VIP: StackDepth = StackDepth::Limited(12);
+    pub const SUPERCHIP: StackDepth = StackDepth::Limited(16);
+
+    // None when unbounded
+    pub fn limit(self) -> Option<usize> {
+        match self {
+            StackDepth::Limited(depth) => Some(depth),
+            #[cfg(feature = "alloc")]
+            StackDepth::Unbounded => None,
+        }
+    }
+}
+
+impl Default for StackDepth {
+    fn default() -> Self {
+        StackDepth::SUPERCHIP
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StackFault {
+    Overflow { depth: usize },
+    Underflow,
+    // Asked for a limit past MAX_STACK_DEPTH
+    DepthTooLarge { depth: usize },
+}
+
+impl fmt::Display for StackFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StackFault::Overflow { depth } => {
+                write!(f, "call stack overflow, more than {depth} nested calls")
+            }
+            StackFault::Underflow => write!(f, "return with an empty call stack"),
+            StackFault::DepthTooLarge { depth } => {
+                write!(f, "stack depth {depth} is more than {MAX_STACK_DEPTH}")
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CallStack {
+    depth: StackDepth,
+    // Popped entries are zeroed so that equal stacks compare equal
+    return_addresses: [u16; MAX_STACK_DEPTH],
+    len: usize,
+    // Unbounded calls nested deeper than MAX_STACK_DEPTH
+    #[cfg(feature = "alloc")]
+    spilled: Vec<u16>,
+}
+
+impl CallStack {
+    pub fn new(depth: StackDepth) -> Result<Self, StackFault> {
+        Self::check_depth(depth)?;
+        Ok(CallStack {
+            depth,
+            return_addresses: [0; MAX_STACK_DEPTH],
+            len: 0,
+            #[cfg(feature = "alloc")]
+            spilled: Vec::new(),
+        })
+    }
+
+    fn check_depth(depth: StackDepth) -> Result<(), StackFault> {
+        match depth.limit() {
+            Some(depth) if depth > MAX_STACK_DEPTH => Err(StackFault::DepthTooLarge { depth }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn depth(&self) -> StackDepth {
+        self.depth
+    }
+
+    // Existing return addresses are kept, the new depth applies to the next push
+    pub fn set_depth(&mut self, depth: StackDepth) -> Result<(), StackFault> {
+        Self::check_depth(depth)?;
+        self.depth = depth;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        #[cfg(feature = "alloc")]
+        return self.len + self.spilled.len();
+        #[cfg(not(feature = "alloc"))]
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn push(&mut self, return_address: u16) -> Result<(), StackFault> {
+        if let Some(depth) = self.depth.limit() {
+            if self.len() >= depth {
+                return Err(StackFault::Overflow { depth });
+            }
+        }
+        if self.len < MAX_STACK_DEPTH {
+            self.return_addresses[self.len] = return_address;
+            self.len += 1;
+        } else {
+            // Only reachable with an unbounded depth
+            #[cfg(feature = "alloc")]
+            self.spilled.push(return_address);
+        }
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Result<u16, StackFault> {
+        #[cfg(feature = "alloc")]
+        if let Some(return_address) = self.spilled.pop() {
+            return Ok(return_address);
+        }
+        if self.len == 0 {
+            return Err(StackFault::Underflow);
+        }
+        self.len -= 1;
+        Ok(core::mem::take(&mut self.return_addresses[self.len]))
+    }
+
+    pub fn clear(&mut self) {
+        self.return_addresses.fill(0);
+        self.len = 0;
+        #[cfg(feature = "alloc")]
+        self.spilled.clear();
+    }
+
+    // Return addresses from the innermost call outwards
+    pub fn backtrace(&self) -> impl Iterator<Item = u16> + '_ {
+        let inline = self.return_addresses[..self.len].iter().rev().copied();
+        #[cfg(feature = "alloc")]
+        return self.spilled.iter().rev().copied().chain(inline);
+        #[cfg(not(feature = "alloc"))]
+        inline
+    }
+}
+
+impl Default for CallStack {
+    fn default() -> Self {
+        CallStack::new(StackDepth::default()).expect("the default depth is supported")
+    }
+}
@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod test {
+    use crate::emulator::call_stack::*;
+
+    #[test]
+    fn returns_in_reverse_call_order() {
+        let mut stack = CallStack::default();
+        stack.push(0x202).unwrap();
+        stack.push(0x30A).unwrap();
+
+        assert_eq!(stack.backtrace().collect::<Vec<_>>(), [0x30A, 0x202]);
+        assert_eq!(stack.pop(), Ok(0x30A));
+        assert_eq!(stack.pop(), Ok(0x202));
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn limited_depth_overflows() {
+        let mut stack = CallStack::new(StackDepth::COSMAC_VIP).unwrap();
+        for address in 0..12 {
+            stack.push(address).unwrap();
+        }
+
+        assert_eq!(stack.push(12), Err(StackFault::Overflow { depth: 12 }));
+        assert_eq!(stack.len(), 12);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn unbounded_depth_never_overflows() {
+        let mut stack = CallStack::new(StackDepth::Unbounded).unwrap();
+        for address in 0..1000 {
+            stack.push(address).unwrap();
+        }
+
+        assert_eq!(stack.len(), 1000);
+        assert_eq!(stack.backtrace().next(), Some(999));
+        assert_eq!(stack.backtrace().last(), Some(0));
+        for address in (0..1000).rev() {
+            assert_eq!(stack.pop(), Ok(address));
+        }
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn limited_depth_is_stored_inline() {
+        let mut stack = CallStack::new(StackDepth::Limited(MAX_STACK_DEPTH)).unwrap();
+        for address in 0..MAX_STACK_DEPTH as u16 {
+            stack.push(address).unwrap();
+        }
+
+        assert_eq!(
+            stack.push(0),
+            Err(StackFault::Overflow {
+                depth: MAX_STACK_DEPTH
+            })
+        );
+        stack.clear();
+        assert_eq!(
+            stack,
+            CallStack::new(StackDepth::Limited(MAX_STACK_DEPTH)).unwrap()
+        );
+    }
+
+    #[test]
+    fn limited_depth_past_the_maximum_is_rejected() {
+        let too_deep = StackDepth::Limited(MAX_STACK_DEPTH + 1);
+        let fault = StackFault::DepthTooLarge {
+            depth: MAX_STACK_DEPTH + 1,
+        };
+        assert_eq!(CallStack::new(too_deep), Err(fault));
+
+        let mut stack = CallStack::default();
+        assert_eq!(stack.set_depth(too_deep), Err(fault));
+        assert_eq!(stack.depth(), StackDepth::default());
+    }
+
+    #[test]
+    fn empty_stack_underflows() {
+        let mut stack = CallStack::default();
+
+        assert_eq!(stack.pop(), Err(StackFault::Underflow));
+    }
+}
@@ -0,0 +1,88 @@
+// Read-only snapshots of the CPU for frontends, debuggers and tests. Changing
+// state goes through Emulator::edit so that it stands out at the call site.
+mod tests;
+
+use core::fmt;
+
+use super::bus::{Bus, BusFault};
+use super::observer::Observer;
+use super::platform::Platform;
+use super::{DisplayWait, Emulator, REGISTER_BANK_SIZE};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CpuState {
+    pub program_counter: u16,
+    pub i_register: u16,
+    pub v_registers: [u8; REGISTER_BANK_SIZE],
+    // Number of return addresses on the call stack
+    pub stack_pointer: usize,
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct InvalidRegister {
+    pub register: u8,
+}
+
+impl fmt::Display for InvalidRegister {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "there is no register V{:X}", self.register)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidRegister {}
+
+pub struct CpuStateEditor<'a, PLATFORM: Platform, BUS: Bus, OBSERVER: Observer> {
+    emulator: &'a mut Emulator<PLATFORM, BUS, OBSERVER>,
+}
+
+impl<'a, PLATFORM: Platform, BUS: Bus, OBSERVER: Observer>
+    CpuStateEditor<'a, PLATFORM, BUS, OBSERVER>
+{
+    pub(super) fn new(emulator: &'a mut Emulator<PLATFORM, BUS, OBSERVER>) -> Self {
+        CpuStateEditor { emulator }
+    }
+
+    // Abandons an FX0A key wait or a DXYN vblank wait at the old address
+    pub fn set_program_counter(&mut self, address: u16) -> &mut Self {
+        self.emulator.program_counter = address;
+        self.emulator.waiting_for_key = false;
+        self.emulator.awaiting_key_release = None;
+        self.emulator.display_wait = DisplayWait::Idle;
+        self
+    }
+
+    pub fn set_i_register(&mut self, value: u16) -> &mut Self {
+        self.emulator.i_register = value;
+        self
+    }
+
+    pub fn set_register(&mut self, register: u8, value: u8) -> Result<&mut Self, InvalidRegister> {
+        let slot = self
+            .emulator
+            .v_registers
+            .get_mut(register as usize)
+            .ok_or(InvalidRegister { register })?;
+        *slot = value;
+        Ok(self)
+    }
+
+    pub fn set_delay_timer(&mut self, value: u8) -> &mut Self {
+        self.emulator.delay_timer = value;
+        self
+    }
+
+    pub fn set_sound_timer(&mut self, value: u8) -> &mut Self {
+        self.emulator.sound_timer = value;
+        self
+    }
+
+    // Ignores read-only regions, like a debugger poking memory
+    pub fn write_memory(&mut self, address: u16, data: &[u8]) -> Result<&mut Self, BusFault> {
+        self.emulator.decode_cache.clear();
+        self.emulator.bus.load(address, data)?;
+        Ok(self)
+    }
+}
@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod test {
+    use crate::emulator::bus::BusFault;
+    use crate::emulator::cpu_state::*;
+    use crate::emulator::platform::headless::HeadlessPlatform;
+    use crate::emulator::platform::{KeyState, KeypadNumber};
+    use crate::emulator::quirks::Quirks;
+
+    #[tokio::test]
+    async fn snapshot_reflects_executed_instructions() {
+        // V3 = 0x42, I = 0x123, delay timer = V3, call 0x20A
+        let rom = [0x63, 0x42, 0xA1, 0x23, 0xF3, 0x15, 0x22, 0x0A];
+        let mut emulator = Emulator::new(HeadlessPlatform::new(1));
+        emulator.load_into_memory(&rom).await.unwrap();
+
+        for _ in 0..4 {
+            emulator.run_instruction_loop().await.unwrap();
+        }
+
+        let mut v_registers = [0; 16];
+        v_registers[3] = 0x42;
+        assert_eq!(
+            emulator.cpu_state(),
+            CpuState {
+                program_counter: 0x20A,
+                i_register: 0x123,
+                v_registers,
+                stack_pointer: 1,
+                delay_timer: 0x42,
+                sound_timer: 0,
+            }
+        );
+        let mut memory = [0; 4];
+        emulator.peek_memory(0x200, &mut memory).unwrap();
+        assert_eq!(memory, rom[..4]);
+        assert_eq!(
+            emulator.peek_memory(0xFFE, &mut memory),
+            Err(BusFault::OutOfBounds { address: 0x1000 })
+        );
+    }
+
+    #[tokio::test]
+    async fn edits_take_effect_on_next_instruction() {
+        let mut emulator = Emulator::new(HeadlessPlatform::new(1));
+        emulator.load_into_memory(&[0x12, 0x00]).await.unwrap();
+        emulator.run_instruction_loop().await.unwrap();
+
+        // Replace the decoded jump with V0 += V1
+        emulator
+            .edit()
+            .set_register(0, 2)
+            .unwrap()
+            .set_register(1, 3)
+            .unwrap()
+            .write_memory(0x200, &[0x80, 0x14])
+            .unwrap();
+        emulator.run_instruction_loop().await.unwrap();
+
+        assert_eq!(emulator.register(0), Some(5));
+        assert_eq!(emulator.program_counter(), 0x202);
+    }
+
+    #[tokio::test]
+    async fn moving_the_program_counter_ends_a_key_wait() {
+        // Wait for a key into V0, then into V1
+        let mut emulator = Emulator::new(HeadlessPlatform::new(1));
+        emulator
+            .load_into_memory(&[0xF0, 0x0A, 0xF1, 0x0A])
+            .await
+            .unwrap();
+        emulator
+            .platform_mut()
+            .set_key(KeypadNumber(5), KeyState::On);
+        emulator.run_instruction_loop().await.unwrap();
+
+        // The release of the key pressed during the first wait must not end the second
+        emulator.edit().set_program_counter(0x202);
+        emulator
+            .platform_mut()
+            .set_key(KeypadNumber(5), KeyState::Off);
+        emulator.run_instruction_loop().await.unwrap();
+
+        assert_eq!(emulator.program_counter(), 0x202);
+        assert_eq!(emulator.register(1), Some(0));
+    }
+
+    #[tokio::test]
+    async fn moving_the_program_counter_ends_a_display_wait() {
+        // Draw, which waits for the next frame, then V0 = 7
+        let mut emulator = Emulator::new(HeadlessPlatform::new(1));
+        emulator.set_quirks(Quirks::cosmac_vip());
+        emulator
+            .load_into_memory(&[0xD0, 0x01, 0x60, 0x07])
+            .await
+            .unwrap();
+        emulator.run_instruction_loop().await.unwrap();
+
+        emulator.edit().set_program_counter(0x202);
+        emulator.run_instruction_loop().await.unwrap();
+
+        assert_eq!(emulator.register(0), Some(7));
+        assert_eq!(emulator.program_counter(), 0x204);
+    }
+
+    #[test]
+    fn write_memory_past_end_faults() {
+        let mut emulator = Emulator::new(HeadlessPlatform::new(1));
+
+        assert_eq!(
+            emulator.edit().write_memory(0xFFF, &[1, 2]).err(),
+            Some(BusFault::OutOfBounds { address: 0x1000 })
+        );
+    }
+
+    #[test]
+    fn registers_past_vf_are_rejected() {
+        let mut emulator = Emulator::new(HeadlessPlatform::new(1));
+
+        assert_eq!(emulator.register(0xF), Some(0));
+        assert_eq!(emulator.register(0x10), None);
+        assert_eq!(
+            emulator.edit().set_register(0x10, 1).err(),
+            Some(InvalidRegister { register: 0x10 })
+        );
+    }
+}
@@ -0,0 +1,159 @@
+// Decoded instructions keyed by the address they were fetched from, so each
+// opcode is only parsed once. Writes from the program invalidate the entries
+// they overlap, and writes over code that has already run are reported.
+// The cache and the record of which addresses ran take about 40 KiB, so
+// without the decode-cache feature a stand-in that caches nothing is used.
+#[cfg(feature = "decode-cache")]
+mod tests;
+
+use super::instruction::Instruction;
+
+// Direct mapped, so standard memory never shares an entry and extended memory
+// only costs a decode when two addresses 4 KiB apart alternate
+#[cfg(feature = "decode-cache")]
+const CACHE_SIZE: usize = 0x1000;
+#[cfg(feature = "decode-cache")]
+const ADDRESS_COUNT: usize = 0x10000;
+// Overwritten addresses past this are only counted
+#[cfg(feature = "decode-cache")]
+pub const MAX_CODE_MODIFICATIONS: usize = 64;
+
+#[cfg(feature = "decode-cache")]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CodeModification {
+    // Address of the first instruction that overwrote this code
+    pub written_by: u16,
+    pub writes: u32,
+}
+
+#[cfg(feature = "decode-cache")]
+const NO_MODIFICATION: (u16, CodeModification) = (
+    0,
+    CodeModification {
+        written_by: 0,
+        writes: 0,
+    },
+);
+
+#[cfg(feature = "decode-cache")]
+pub struct DecodeCache {
+    // Tagged with the address they were decoded from
+    entries: [Option<(u16, Instruction)>; CACHE_SIZE],
+    // One bit per address that ran since it was last written by the program,
+    // kept by clear() so that later writes are still reported
+    executed: [u32; ADDRESS_COUNT / 32],
+    // Sorted by address
+    modifications: [(u16, CodeModification); MAX_CODE_MODIFICATIONS],
+    modification_count: usize,
+    untracked_modifications: u32,
+}
+
+#[cfg(feature = "decode-cache")]
+impl DecodeCache {
+    pub fn new() -> Self {
+        DecodeCache {
+            entries: [None; CACHE_SIZE],
+            executed: [0; ADDRESS_COUNT / 32],
+            modifications: [NO_MODIFICATION; MAX_CODE_MODIFICATIONS],
+            modification_count: 0,
+            untracked_modifications: 0,
+        }
+    }
+
+    pub fn get(&self, address: u16) -> Option<Instruction> {
+        match self.entries[address as usize % CACHE_SIZE] {
+            Some((tag, instruction)) if tag == address => Some(instruction),
+            _ => None,
+        }
+    }
+
+    // Called for every decoded instruction before it runs
+    pub fn insert(&mut self, address: u16, instruction: Instruction) {
+        self.entries[address as usize % CACHE_SIZE] = Some((address, instruction));
+        self.executed[address as usize / 32] |= 1 << (address % 32);
+    }
+
+    // A written byte is either half of an instruction starting there or one byte earlier
+    pub fn invalidate(&mut self, address: u16, written_by: u16) {
+        for start in [address.wrapping_sub(1), address] {
+            let entry = &mut self.entries[start as usize % CACHE_SIZE];
+            if entry.is_some_and(|(tag, _)| tag == start) {
+                *entry = None;
+            }
+
+            let executed = &mut self.executed[start as usize / 32];
+            let bit = 1 << (start % 32);
+            if *executed & bit != 0 {
+                *executed &= !bit;
+                self.record_modification(start, written_by);
+            }
+        }
+    }
+
+    fn record_modification(&mut self, address: u16, written_by: u16) {
+        let recorded = &mut self.modifications[..self.modification_count];
+        match recorded.binary_search_by_key(&address, |&(address, _)| address) {
+            Ok(index) => recorded[index].1.writes += 1,
+            Err(_) if self.modification_count == MAX_CODE_MODIFICATIONS => {
+                self.untracked_modifications += 1;
+            }
+            Err(index) => {
+                self.modifications
+                    .copy_within(index..self.modification_count, index + 1);
+                self.modifications[index] = (
+                    address,
+                    CodeModification {
+                        written_by,
+                        writes: 1,
+                    },
+                );
+                self.modification_count += 1;
+            }
+        }
+    }
+
+    // Forgets decoded instructions without reporting them, for writes from
+    // outside the program. Which addresses ran is kept.
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+
+    // Overwritten instruction addresses in ascending order
+    pub fn modifications(&self) -> impl Iterator<Item = (u16, CodeModification)> + '_ {
+        self.modifications[..self.modification_count]
+            .iter()
+            .copied()
+    }
+
+    // Writes over executed code once MAX_CODE_MODIFICATIONS addresses are recorded
+    pub fn untracked_modifications(&self) -> u32 {
+        self.untracked_modifications
+    }
+}
+
+#[cfg(not(feature = "decode-cache"))]
+pub struct DecodeCache;
+
+// Every fetch is decoded and code modifications are not tracked
+#[cfg(not(feature = "decode-cache"))]
+impl DecodeCache {
+    pub fn new() -> Self {
+        DecodeCache
+    }
+
+    pub fn get(&self, _address: u16) -> Option<Instruction> {
+        None
+    }
+
+    pub fn insert(&mut self, _address: u16, _instruction: Instruction) {}
+
+    pub fn invalidate(&mut self, _address: u16, _written_by: u16) {}
+
+    pub fn clear(&mut self) {}
+}
+
+impl Default for DecodeCache {
+    fn default() -> Self {
+        DecodeCache::new()
+    }
+}
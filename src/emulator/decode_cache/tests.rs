@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod test {
+    use crate::emulator::decode_cache::*;
+
+    #[test]
+    fn write_invalidates_both_overlapping_instructions() {
+        let mut cache = DecodeCache::new();
+        cache.insert(0x200, Instruction::ClearDisplay);
+        cache.insert(0x201, Instruction::ClearDisplay);
+        cache.insert(0x202, Instruction::ClearDisplay);
+
+        cache.invalidate(0x201, 0x300);
+
+        assert_eq!(cache.get(0x200), None);
+        assert_eq!(cache.get(0x201), None);
+        assert_eq!(cache.get(0x202), Some(Instruction::ClearDisplay));
+    }
+
+    #[test]
+    fn only_writes_over_decoded_code_are_reported() {
+        let mut cache = DecodeCache::new();
+        cache.insert(0x200, Instruction::ClearDisplay);
+
+        cache.invalidate(0x400, 0x202);
+        cache.invalidate(0x200, 0x202);
+        cache.insert(0x200, Instruction::ClearDisplay);
+        cache.invalidate(0x200, 0x204);
+
+        let modifications: Vec<_> = cache.modifications().collect();
+        assert_eq!(
+            modifications,
+            [(
+                0x200,
+                CodeModification {
+                    written_by: 0x202,
+                    writes: 2
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn clear_is_not_reported() {
+        let mut cache = DecodeCache::new();
+        cache.insert(0x200, Instruction::ClearDisplay);
+
+        cache.clear();
+
+        assert_eq!(cache.get(0x200), None);
+        assert_eq!(cache.modifications().count(), 0);
+    }
+
+    #[test]
+    fn writes_after_clear_are_still_reported() {
+        let mut cache = DecodeCache::new();
+        cache.insert(0x200, Instruction::ClearDisplay);
+
+        cache.clear();
+        cache.invalidate(0x200, 0x202);
+
+        assert_eq!(cache.modifications().count(), 1);
+    }
+
+    #[test]
+    fn addresses_4k_apart_share_an_entry() {
+        let mut cache = DecodeCache::new();
+        cache.insert(0x0200, Instruction::ClearDisplay);
+        cache.insert(0x1200, Instruction::ReturnFromSubroutine);
+
+        assert_eq!(cache.get(0x0200), None);
+        assert_eq!(cache.get(0x1200), Some(Instruction::ReturnFromSubroutine));
+    }
+
+    #[test]
+    fn modifications_past_the_limit_are_counted() {
+        let mut cache = DecodeCache::new();
+        let addresses = (0..MAX_CODE_MODIFICATIONS as u16 + 2).map(|index| 0x800 - 2 * index);
+        for address in addresses {
+            cache.insert(address, Instruction::ClearDisplay);
+            cache.invalidate(address, 0x202);
+        }
+
+        let recorded: Vec<u16> = cache.modifications().map(|(address, _)| address).collect();
+        assert_eq!(recorded.len(), MAX_CODE_MODIFICATIONS);
+        assert!(recorded.is_sorted());
+        assert_eq!(cache.untracked_modifications(), 2);
+    }
+}
@@ -0,0 +1,81 @@
+use super::platform::{Pixel, PixelState, Platform};
+
+pub const DISPLAY_WIDTH: u8 = 64;
+pub const DISPLAY_HEIGHT: u8 = 32;
+const FRAMEBUFFER_SIZE: usize = DISPLAY_WIDTH as usize * DISPLAY_HEIGHT as usize;
+
+// One byte per pixel, row major, 1 for on and 0 for off
+#[derive(Debug, PartialEq, Clone)]
+pub struct Framebuffer {
+    pixels: [u8; FRAMEBUFFER_SIZE],
+}
+
+impl Framebuffer {
+    pub fn new() -> Self {
+        Framebuffer {
+            pixels: [0; FRAMEBUFFER_SIZE],
+        }
+    }
+
+    pub async fn capture<PLATFORM: Platform>(platform: &PLATFORM) -> Self {
+        let mut framebuffer = Framebuffer::new();
+        let width = platform.get_display_width().await.min(DISPLAY_WIDTH);
+        let height = platform.get_display_height().await.min(DISPLAY_HEIGHT);
+
+        for row in 0..height {
+            for column in 0..width {
+                let pixel = Pixel { column, row };
+                framebuffer.set_pixel(pixel, platform.get_pixel(pixel).await);
+            }
+        }
+
+        framebuffer
+    }
+
+    pub fn width(&self) -> u8 {
+        DISPLAY_WIDTH
+    }
+
+    pub fn height(&self) -> u8 {
+        DISPLAY_HEIGHT
+    }
+
+    pub fn clear(&mut self) {
+        self.pixels.fill(0);
+    }
+
+    pub fn get_pixel(&self, pixel: Pixel) -> PixelState {
+        match self.pixels[Self::index(pixel)] {
+            0 => PixelState::Off,
+            _ => PixelState::On,
+        }
+    }
+
+    pub fn set_pixel(&mut self, pixel: Pixel, state: PixelState) {
+        self.pixels[Self::index(pixel)] = match state {
+            PixelState::On => 1,
+            PixelState::Off => 0,
+        };
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    // FNV-1a over the pixels, the same on every platform and release
+    pub fn hash(&self) -> u64 {
+        self.pixels.iter().fold(0xcbf29ce484222325, |hash, &pixel| {
+            (hash ^ pixel as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+
+    fn index(pixel: Pixel) -> usize {
+        pixel.row as usize * DISPLAY_WIDTH as usize + pixel.column as usize
+    }
+}
+
+impl Default for Framebuffer {
+    fn default() -> Self {
+        Framebuffer::new()
+    }
+}
@@ -1,10 +1,31 @@
 pub mod parser;
+pub mod table;
 
 mod tests;
 
+use core::fmt;
+
 use super::types::{EightBitValue, FourBitValue, MemoryAddress, RegisterNumber, TwelveBitValue};
 
-#[derive(Debug, PartialEq)]
+// Each level is a superset of the ones before it
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum InstructionSet {
+    Chip8,
+    SuperChip,
+    XoChip,
+}
+
+// Turns a big endian opcode into an instruction, None for invalid opcodes.
+// Shared by every emulator, so that emulators can move between threads
+pub trait Decoder: Sync {
+    fn decode(&self, opcode: &[u8; 2]) -> Option<Instruction>;
+
+    fn instruction_set(&self) -> InstructionSet {
+        InstructionSet::Chip8
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Instruction {
     // OpCode 0
     System {
@@ -74,7 +95,7 @@ pub enum Instruction {
         source: RegisterNumber,
         destination: RegisterNumber,
     },
-    SubtractDestinationFromSource {
+    SubtractSourceFromDestination {
         source: RegisterNumber,
         destination: RegisterNumber,
     },
@@ -82,7 +103,7 @@ pub enum Instruction {
         source: RegisterNumber,
         destination: RegisterNumber,
     },
-    SubtractSourceFromDestination {
+    SubtractDestinationFromSource {
         source: RegisterNumber,
         destination: RegisterNumber,
     },
@@ -157,3 +178,123 @@ pub enum Instruction {
         end: RegisterNumber,
     },
 }
+
+// Cowgod's mnemonics, e.g. "LD V3, 0x2A" or "DRW V0, V1, 5"
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let v = |register: &RegisterNumber| register.0 .0;
+        let address = |address: &MemoryAddress| u16::from(*address);
+
+        match self {
+            Instruction::System { address: a } => write!(f, "SYS {:#05X}", address(a)),
+            Instruction::ClearDisplay => write!(f, "CLS"),
+            Instruction::ReturnFromSubroutine => write!(f, "RET"),
+            Instruction::Jump { address: a } => write!(f, "JP {:#05X}", address(a)),
+            Instruction::Call { address: a } => write!(f, "CALL {:#05X}", address(a)),
+            Instruction::SkipNextInstructionIfMatch {
+                read_value_from,
+                immediate,
+            } => write!(f, "SE V{:X}, {:#04X}", v(read_value_from), immediate.0),
+            Instruction::SkipNextInstructionIfNotMatch {
+                read_value_from,
+                immediate,
+            } => write!(f, "SNE V{:X}, {:#04X}", v(read_value_from), immediate.0),
+            Instruction::SkipNextInstructionIfValuesMatch { lhs, rhs } => {
+                write!(f, "SE V{:X}, V{:X}", v(lhs), v(rhs))
+            }
+            Instruction::LoadImmediateToRegister {
+                immediate,
+                destination,
+            } => write!(f, "LD V{:X}, {:#04X}", v(destination), immediate.0),
+            Instruction::AddImmediateToRegister {
+                immediate,
+                destination,
+            } => write!(f, "ADD V{:X}, {:#04X}", v(destination), immediate.0),
+            Instruction::CopyRegisterValue {
+                source,
+                destination,
+            } => write!(f, "LD V{:X}, V{:X}", v(destination), v(source)),
+            Instruction::BitwiseOrRegisters {
+                source,
+                destination,
+            } => write!(f, "OR V{:X}, V{:X}", v(destination), v(source)),
+            Instruction::BitwiseAndRegisters {
+                source,
+                destination,
+            } => write!(f, "AND V{:X}, V{:X}", v(destination), v(source)),
+            Instruction::BitwiseXorRegisters {
+                source,
+                destination,
+            } => write!(f, "XOR V{:X}, V{:X}", v(destination), v(source)),
+            Instruction::AddRegisters {
+                source,
+                destination,
+            } => write!(f, "ADD V{:X}, V{:X}", v(destination), v(source)),
+            Instruction::SubtractSourceFromDestination {
+                source,
+                destination,
+            } => write!(f, "SUB V{:X}, V{:X}", v(destination), v(source)),
+            Instruction::ShiftRightRegisters {
+                source,
+                destination,
+            } => write!(f, "SHR V{:X}, V{:X}", v(destination), v(source)),
+            Instruction::SubtractDestinationFromSource {
+                source,
+                destination,
+            } => write!(f, "SUBN V{:X}, V{:X}", v(destination), v(source)),
+            Instruction::ShiftLeftRegisters {
+                source,
+                destination,
+            } => write!(f, "SHL V{:X}, V{:X}", v(destination), v(source)),
+            Instruction::SkipNextInstructionIfValuesDoNotMatch { lhs, rhs } => {
+                write!(f, "SNE V{:X}, V{:X}", v(lhs), v(rhs))
+            }
+            Instruction::LoadToIRegister { immediate } => write!(f, "LD I, {:#05X}", immediate.0),
+            Instruction::JumpToSumOfV0ValueAndImmediate { immediate } => {
+                write!(f, "JP V0, {:#05X}", immediate.0)
+            }
+            Instruction::LoadBitwiseAndOfRandomByteAndImmediate {
+                destination,
+                immediate,
+            } => write!(f, "RND V{:X}, {:#04X}", v(destination), immediate.0),
+            Instruction::DrawSpritesFromMemory {
+                read_x_axis_from,
+                read_y_axis_from,
+                bytes_to_read_from_i_register,
+            } => write!(
+                f,
+                "DRW V{:X}, V{:X}, {}",
+                v(read_x_axis_from),
+                v(read_y_axis_from),
+                bytes_to_read_from_i_register.0
+            ),
+            Instruction::SkipNextInstructionIfKeyIsPressed {
+                read_key_number_from,
+            } => write!(f, "SKP V{:X}", v(read_key_number_from)),
+            Instruction::SkipNextInstructionIfKeyIsNotPressed {
+                read_key_number_from,
+            } => write!(f, "SKNP V{:X}", v(read_key_number_from)),
+            Instruction::LoadDelayTimerIntoRegister { destination } => {
+                write!(f, "LD V{:X}, DT", v(destination))
+            }
+            Instruction::AwaitKeyPressAndLoadIntoRegister { destination } => {
+                write!(f, "LD V{:X}, K", v(destination))
+            }
+            Instruction::LoadIntoDelayTimer { source } => write!(f, "LD DT, V{:X}", v(source)),
+            Instruction::LoadIntoSoundTimer { source } => write!(f, "LD ST, V{:X}", v(source)),
+            Instruction::AddValueToIRegister { source } => write!(f, "ADD I, V{:X}", v(source)),
+            Instruction::LoadSpriteLocationForValueIntoIRegister { source } => {
+                write!(f, "LD F, V{:X}", v(source))
+            }
+            Instruction::LoadBinaryCodedDecimalValueIntoSequenceStartingAtIRegisterValue {
+                source,
+            } => write!(f, "LD B, V{:X}", v(source)),
+            Instruction::LoadValuesFromV0ToRegisterIntoSequenceStartingAtIRegisterValue { end } => {
+                write!(f, "LD [I], V{:X}", v(end))
+            }
+            Instruction::LoadSequenceStartingAtIRegisterValueIntoV0ToRegister { end } => {
+                write!(f, "LD V{:X}, [I]", v(end))
+            }
+        }
+    }
+}
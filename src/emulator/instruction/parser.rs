@@ -3,7 +3,15 @@ pub fn parse_instruction(input: &[u8; 2]) -> Option<Instruction> {
     Some(result)
 }
 
-use crate::emulator::instruction::Instruction;
+pub struct NomDecoder;
+
+impl Decoder for NomDecoder {
+    fn decode(&self, opcode: &[u8; 2]) -> Option<Instruction> {
+        parse_instruction(opcode)
+    }
+}
+
+use crate::emulator::instruction::{Decoder, Instruction};
 use crate::emulator::types::{
     EightBitValue, FourBitValue, MemoryAddress, RegisterNumber, TwelveBitValue,
 };
@@ -164,11 +172,11 @@ fn opcode_8(input: (&[u8], usize)) -> IResult<(&[u8], usize), Instruction> {
             },
         ))
     };
-    let subtract_destination_from_source = |input| {
+    let subtract_source_from_destination = |input| {
         let (input, (destination, source)) = sub_opcode::<5>(input)?;
         Ok((
             input,
-            Instruction::SubtractDestinationFromSource {
+            Instruction::SubtractSourceFromDestination {
                 source,
                 destination,
             },
@@ -184,11 +192,11 @@ fn opcode_8(input: (&[u8], usize)) -> IResult<(&[u8], usize), Instruction> {
             },
         ))
     };
-    let subtract_source_from_destination = |input| {
+    let subtract_destination_from_source = |input| {
         let (input, (destination, source)) = sub_opcode::<7>(input)?;
         Ok((
             input,
-            Instruction::SubtractSourceFromDestination {
+            Instruction::SubtractDestinationFromSource {
                 source,
                 destination,
             },
@@ -211,9 +219,9 @@ fn opcode_8(input: (&[u8], usize)) -> IResult<(&[u8], usize), Instruction> {
         bitwise_and,
         bitwise_xor,
         add,
-        subtract_destination_from_source,
-        shift_right,
         subtract_source_from_destination,
+        shift_right,
+        subtract_destination_from_source,
         shift_left,
     ));
     preceded(match_opcode::<8>, sub_opcodes).parse(input)
@@ -0,0 +1,141 @@
+// Decodes with a match on the top nibble and masks for the operands, without
+// going through nom
+mod tests;
+
+use super::{Decoder, Instruction};
+use crate::emulator::types::{
+    EightBitValue, FourBitValue, MemoryAddress, RegisterNumber, TwelveBitValue,
+};
+
+pub struct TableDecoder;
+
+impl Decoder for TableDecoder {
+    fn decode(&self, opcode: &[u8; 2]) -> Option<Instruction> {
+        decode(u16::from_be_bytes(*opcode))
+    }
+}
+
+pub fn decode(opcode: u16) -> Option<Instruction> {
+    let x = register((opcode >> 8) as u8);
+    let y = register((opcode >> 4) as u8);
+    let n = FourBitValue((opcode & 0xF) as u8);
+    let nn = EightBitValue(opcode as u8);
+    let nnn = TwelveBitValue(opcode & 0xFFF);
+    let address = MemoryAddress(nnn);
+
+    let instruction = match opcode >> 12 {
+        0x0 => match opcode & 0xFFF {
+            0x0E0 => Instruction::ClearDisplay,
+            0x0EE => Instruction::ReturnFromSubroutine,
+            _ => Instruction::System { address },
+        },
+        0x1 => Instruction::Jump { address },
+        0x2 => Instruction::Call { address },
+        0x3 => Instruction::SkipNextInstructionIfMatch {
+            read_value_from: x,
+            immediate: nn,
+        },
+        0x4 => Instruction::SkipNextInstructionIfNotMatch {
+            read_value_from: x,
+            immediate: nn,
+        },
+        0x5 if opcode & 0xF == 0 => {
+            Instruction::SkipNextInstructionIfValuesMatch { lhs: x, rhs: y }
+        }
+        0x6 => Instruction::LoadImmediateToRegister {
+            immediate: nn,
+            destination: x,
+        },
+        0x7 => Instruction::AddImmediateToRegister {
+            immediate: nn,
+            destination: x,
+        },
+        0x8 => {
+            let (source, destination) = (y, x);
+            match opcode & 0xF {
+                0x0 => Instruction::CopyRegisterValue {
+                    source,
+                    destination,
+                },
+                0x1 => Instruction::BitwiseOrRegisters {
+                    source,
+                    destination,
+                },
+                0x2 => Instruction::BitwiseAndRegisters {
+                    source,
+                    destination,
+                },
+                0x3 => Instruction::BitwiseXorRegisters {
+                    source,
+                    destination,
+                },
+                0x4 => Instruction::AddRegisters {
+                    source,
+                    destination,
+                },
+                0x5 => Instruction::SubtractSourceFromDestination {
+                    source,
+                    destination,
+                },
+                0x6 => Instruction::ShiftRightRegisters {
+                    source,
+                    destination,
+                },
+                0x7 => Instruction::SubtractDestinationFromSource {
+                    source,
+                    destination,
+                },
+                0xE => Instruction::ShiftLeftRegisters {
+                    source,
+                    destination,
+                },
+                _ => return None,
+            }
+        }
+        0x9 if opcode & 0xF == 0 => {
+            Instruction::SkipNextInstructionIfValuesDoNotMatch { lhs: x, rhs: y }
+        }
+        0xA => Instruction::LoadToIRegister { immediate: nnn },
+        0xB => Instruction::JumpToSumOfV0ValueAndImmediate { immediate: nnn },
+        0xC => Instruction::LoadBitwiseAndOfRandomByteAndImmediate {
+            destination: x,
+            immediate: nn,
+        },
+        0xD => Instruction::DrawSpritesFromMemory {
+            read_x_axis_from: x,
+            read_y_axis_from: y,
+            bytes_to_read_from_i_register: n,
+        },
+        0xE => match opcode & 0xFF {
+            0x9E => Instruction::SkipNextInstructionIfKeyIsPressed {
+                read_key_number_from: x,
+            },
+            0xA1 => Instruction::SkipNextInstructionIfKeyIsNotPressed {
+                read_key_number_from: x,
+            },
+            _ => return None,
+        },
+        0xF => match opcode & 0xFF {
+            0x07 => Instruction::LoadDelayTimerIntoRegister { destination: x },
+            0x0A => Instruction::AwaitKeyPressAndLoadIntoRegister { destination: x },
+            0x15 => Instruction::LoadIntoDelayTimer { source: x },
+            0x18 => Instruction::LoadIntoSoundTimer { source: x },
+            0x1E => Instruction::AddValueToIRegister { source: x },
+            0x29 => Instruction::LoadSpriteLocationForValueIntoIRegister { source: x },
+            0x33 => Instruction::LoadBinaryCodedDecimalValueIntoSequenceStartingAtIRegisterValue {
+                source: x,
+            },
+            0x55 => Instruction::LoadValuesFromV0ToRegisterIntoSequenceStartingAtIRegisterValue {
+                end: x,
+            },
+            0x65 => Instruction::LoadSequenceStartingAtIRegisterValueIntoV0ToRegister { end: x },
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(instruction)
+}
+
+fn register(nibble: u8) -> RegisterNumber {
+    RegisterNumber(FourBitValue(nibble & 0xF))
+}
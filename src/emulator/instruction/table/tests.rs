@@ -0,0 +1,17 @@
+#[cfg(test)]
+mod test {
+    use crate::emulator::instruction::parser::NomDecoder;
+    use crate::emulator::instruction::table::*;
+
+    #[test]
+    fn agrees_with_nom_decoder_on_every_opcode() {
+        for opcode in 0..=u16::MAX {
+            let bytes = opcode.to_be_bytes();
+            assert_eq!(
+                TableDecoder.decode(&bytes),
+                NomDecoder.decode(&bytes),
+                "opcode {opcode:04X}"
+            );
+        }
+    }
+}
@@ -1,4 +1,5 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use crate::emulator::instruction::*;
     use crate::emulator::instruction::parser::*;
@@ -217,7 +218,7 @@ mod tests {
     #[test]
     fn subtract_destination_from_source_values() {
         // Act
-        let parsed = instruction(b"\x81\x25");
+        let parsed = instruction(b"\x81\x27");
 
         // Verify
         let expected_value = Instruction::SubtractDestinationFromSource {
@@ -245,7 +246,7 @@ mod tests {
     #[test]
     fn subtract_source_from_destination_values() {
         // Act
-        let parsed = instruction(b"\x81\x27");
+        let parsed = instruction(b"\x81\x25");
 
         // Verify
         let expected_value = Instruction::SubtractSourceFromDestination {
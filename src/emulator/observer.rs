@@ -0,0 +1,28 @@
+// Callbacks for embedders that want to follow what the emulator is doing.
+// Every method does nothing by default, and the unit observer compiles away.
+mod tests;
+
+use super::instruction::Instruction;
+use super::platform::KeypadNumber;
+use super::Fault;
+
+pub trait Observer {
+    // Before the instruction fetched from address runs
+    fn on_instruction(&mut self, _address: u16, _instruction: &Instruction) {}
+
+    // x and y are the register values before wrapping
+    fn on_draw(&mut self, _x: u8, _y: u8, _rows: u8, _collided: bool) {}
+
+    fn on_sound_start(&mut self) {}
+
+    fn on_sound_stop(&mut self) {}
+
+    // FX0A started waiting, it keeps executing until a key is pressed and released
+    fn on_key_wait(&mut self) {}
+
+    fn on_key_wait_end(&mut self, _key: KeypadNumber) {}
+
+    fn on_fault(&mut self, _fault: &Fault) {}
+}
+
+impl Observer for () {}
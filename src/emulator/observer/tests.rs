@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod test {
+    use crate::emulator::bus::MemoryBus;
+    use crate::emulator::instruction::Instruction;
+    use crate::emulator::observer::*;
+    use crate::emulator::platform::headless::HeadlessPlatform;
+    use crate::emulator::platform::KeyState;
+    use crate::emulator::{Emulator, FaultKind};
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Instruction(u16, Instruction),
+        Draw(u8, u8, u8, bool),
+        SoundStart,
+        SoundStop,
+        KeyWait,
+        KeyWaitEnd(KeypadNumber),
+        Fault(FaultKind),
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<Event>,
+    }
+
+    impl Observer for Recorder {
+        fn on_instruction(&mut self, address: u16, instruction: &Instruction) {
+            self.events.push(Event::Instruction(address, *instruction));
+        }
+        fn on_draw(&mut self, x: u8, y: u8, rows: u8, collided: bool) {
+            self.events.push(Event::Draw(x, y, rows, collided));
+        }
+        fn on_sound_start(&mut self) {
+            self.events.push(Event::SoundStart);
+        }
+        fn on_sound_stop(&mut self) {
+            self.events.push(Event::SoundStop);
+        }
+        fn on_key_wait(&mut self) {
+            self.events.push(Event::KeyWait);
+        }
+        fn on_key_wait_end(&mut self, key: KeypadNumber) {
+            self.events.push(Event::KeyWaitEnd(key));
+        }
+        fn on_fault(&mut self, fault: &Fault) {
+            self.events.push(Event::Fault(fault.kind));
+        }
+    }
+
+    fn emulator() -> Emulator<HeadlessPlatform, MemoryBus, Recorder> {
+        Emulator::new(HeadlessPlatform::new(1)).with_observer(Recorder::default())
+    }
+
+    #[tokio::test]
+    async fn reports_instructions_draws_and_faults() {
+        // I = font "0", draw twice at V0, V1, then an invalid opcode
+        let rom = [0xA0, 0x50, 0xD0, 0x15, 0xD0, 0x15, 0xFF, 0xFF];
+        let mut emulator = emulator();
+        emulator.load_into_memory(&rom).await.unwrap();
+
+        for _ in 0..3 {
+            emulator.run_instruction_loop().await.unwrap();
+        }
+        assert!(emulator.run_instruction_loop().await.is_err());
+
+        let events = &emulator.observer().events;
+        assert_eq!(events.len(), 6);
+        assert!(matches!(events[0], Event::Instruction(0x200, _)));
+        assert_eq!(events[2], Event::Draw(0, 0, 5, false));
+        assert_eq!(events[4], Event::Draw(0, 0, 5, true));
+        assert_eq!(
+            events[5],
+            Event::Fault(FaultKind::InvalidOpcode { opcode: 0xFFFF })
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_sound_transitions_once() {
+        // V0 = 2, sound timer = V0, loop
+        let rom = [0x60, 0x02, 0xF0, 0x18, 0x12, 0x04];
+        let mut emulator = emulator();
+        emulator.load_into_memory(&rom).await.unwrap();
+
+        for _ in 0..4 {
+            emulator.run_frame().await.unwrap();
+        }
+
+        let sound: Vec<_> = emulator
+            .observer()
+            .events
+            .iter()
+            .filter(|event| matches!(event, Event::SoundStart | Event::SoundStop))
+            .collect();
+        assert_eq!(sound, [&Event::SoundStart, &Event::SoundStop]);
+    }
+
+    #[tokio::test]
+    async fn reports_key_wait_once_per_wait() {
+        // Wait for a key into V3
+        let mut emulator = emulator();
+        emulator.load_into_memory(&[0xF3, 0x0A]).await.unwrap();
+        for _ in 0..3 {
+            emulator.run_frame().await.unwrap();
+        }
+
+        let key = KeypadNumber(0x7);
+        emulator.platform_mut().set_key(key, KeyState::On);
+        emulator.run_frame().await.unwrap();
+        emulator.platform_mut().set_key(key, KeyState::Off);
+        emulator.run_instruction_loop().await.unwrap();
+
+        let waits: Vec<_> = emulator
+            .observer()
+            .events
+            .iter()
+            .filter(|event| matches!(event, Event::KeyWait | Event::KeyWaitEnd(_)))
+            .collect();
+        assert_eq!(waits, [&Event::KeyWait, &Event::KeyWaitEnd(key)]);
+    }
+}
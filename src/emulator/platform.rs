@@ -1,3 +1,7 @@
+pub mod headless;
+
+use core::time::Duration;
+
 use derive_more::{From, Into};
 
 #[derive(Debug, PartialEq, Clone, Copy, From, Into)]
@@ -25,7 +29,10 @@ pub enum BuzzerState {
     Off,
 }
 
-#[async_trait::async_trait]
+pub const TIMER_FREQUENCY: u32 = 60;
+pub const FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / TIMER_FREQUENCY as u64);
+
+#[allow(async_fn_in_trait)]
 pub trait Platform {
     // Display
     async fn get_display_width(&self) -> u8;
@@ -36,17 +43,38 @@ pub trait Platform {
     async fn set_pixel(&mut self, pixel: Pixel, state: PixelState);
 
     // Keypad
-    async fn block_for_any_keypress(&mut self) -> KeyState;
     async fn read_keypress_state(&self, key: KeypadNumber) -> KeyState;
+    async fn get_pressed_key(&self) -> Option<KeypadNumber> {
+        for key in (0..KEYPAD_COUNT).map(KeypadNumber) {
+            if self.read_keypress_state(key).await == KeyState::On {
+                return Some(key);
+            }
+        }
+        None
+    }
 
     // Buzzer
     async fn set_buzzer(&mut self, state: BuzzerState);
-}
 
+    // Random, without std the platform must provide its own source
+    #[cfg(feature = "std")]
+    async fn random_byte(&mut self) -> u8 {
+        rand::random()
+    }
+    #[cfg(not(feature = "std"))]
+    async fn random_byte(&mut self) -> u8;
+
+    // Time, without std the platform must pace frames itself
+    #[cfg(feature = "std")]
+    async fn wait_for_next_frame(&mut self) {
+        async_io::Timer::after(FRAME_DURATION).await;
+    }
+    #[cfg(not(feature = "std"))]
+    async fn wait_for_next_frame(&mut self);
+}
 
 impl From<KeypadNumber> for usize {
     fn from(item: KeypadNumber) -> Self {
-        let value: u8 = item.0.into();
-        value as usize
+        item.0 as usize
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,82 @@
+use crate::emulator::framebuffer::{Framebuffer, DISPLAY_HEIGHT, DISPLAY_WIDTH};
+
+use super::*;
+
+type Keypad = [KeyState; KEYPAD_COUNT as usize];
+
+// Runs without a display or clock, frames are paced by the caller and the
+// random source is seeded so that runs are reproducible
+pub struct HeadlessPlatform {
+    framebuffer: Framebuffer,
+    keypad: Keypad,
+    buzzer: BuzzerState,
+    random_state: u32,
+}
+
+impl HeadlessPlatform {
+    pub fn new(seed: u32) -> Self {
+        HeadlessPlatform {
+            framebuffer: Framebuffer::new(),
+            keypad: [KeyState::Off; KEYPAD_COUNT as usize],
+            buzzer: BuzzerState::Off,
+            // Xorshift gets stuck on zero
+            random_state: seed.max(1),
+        }
+    }
+
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    pub fn set_key(&mut self, key: KeypadNumber, state: KeyState) {
+        let index: usize = key.into();
+        self.keypad[index] = state;
+    }
+
+    pub fn buzzer(&self) -> BuzzerState {
+        self.buzzer
+    }
+}
+
+impl Platform for HeadlessPlatform {
+    // Display
+    async fn get_display_width(&self) -> u8 {
+        DISPLAY_WIDTH
+    }
+    async fn get_display_height(&self) -> u8 {
+        DISPLAY_HEIGHT
+    }
+    async fn clear_display(&mut self) {
+        self.framebuffer.clear();
+    }
+    async fn get_pixel(&self, pixel: Pixel) -> PixelState {
+        self.framebuffer.get_pixel(pixel)
+    }
+    async fn set_pixel(&mut self, pixel: Pixel, state: PixelState) {
+        self.framebuffer.set_pixel(pixel, state);
+    }
+
+    // Keypad
+    async fn read_keypress_state(&self, key: KeypadNumber) -> KeyState {
+        let index: usize = key.into();
+        self.keypad[index]
+    }
+
+    // Buzzer
+    async fn set_buzzer(&mut self, state: BuzzerState) {
+        self.buzzer = state;
+    }
+
+    // Random
+    async fn random_byte(&mut self) -> u8 {
+        let mut x = self.random_state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.random_state = x;
+        (x >> 24) as u8
+    }
+
+    // Time
+    async fn wait_for_next_frame(&mut self) {}
+}
@@ -0,0 +1,339 @@
+// Single instructions run on random machine states, checked against a small
+// reference interpreter written straight from the instruction descriptions
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use std::fmt;
+
+    use proptest::prelude::*;
+
+    use super::super::bus::Bus;
+    use super::super::font::{FONT_ADDRESS, FONT_CHARACTER_SIZE};
+    use super::super::platform::headless::HeadlessPlatform;
+    use super::super::quirks::Quirks;
+    use super::super::{Emulator, DATA_START_ADDRESS};
+
+    const MEMORY_SIZE: usize = 0x1000;
+    const STACK_DEPTH: usize = 16;
+
+    #[derive(Clone, PartialEq)]
+    struct State {
+        program_counter: u16,
+        i_register: u16,
+        v_registers: [u8; 16],
+        delay_timer: u8,
+        sound_timer: u8,
+        stack: Vec<u16>,
+        memory: Vec<u8>,
+    }
+
+    // Only the program area bytes that are set, a full dump hides the difference
+    struct Program<'a>(&'a [u8]);
+
+    impl fmt::Debug for Program<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let set_bytes = (DATA_START_ADDRESS as usize..self.0.len())
+                .filter(|&address| self.0[address] != 0)
+                .map(|address| (format!("{address:#05X}"), self.0[address]));
+            f.debug_map().entries(set_bytes).finish()
+        }
+    }
+
+    impl fmt::Debug for State {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("State")
+                .field(
+                    "program_counter",
+                    &format_args!("{:#05X}", self.program_counter),
+                )
+                .field("i_register", &format_args!("{:#05X}", self.i_register))
+                .field("v_registers", &self.v_registers)
+                .field("delay_timer", &self.delay_timer)
+                .field("sound_timer", &self.sound_timer)
+                .field("stack", &self.stack)
+                .field("program", &Program(&self.memory))
+                .finish()
+        }
+    }
+
+    // Faults are compared by whether they happen, not where
+    #[derive(Debug, PartialEq)]
+    struct Fault;
+
+    fn read(memory: &[u8], address: u16) -> Result<u8, Fault> {
+        memory.get(address as usize).copied().ok_or(Fault)
+    }
+
+    fn write(memory: &mut [u8], address: u16, value: u8) -> Result<(), Fault> {
+        if address < DATA_START_ADDRESS {
+            return Err(Fault);
+        }
+        *memory.get_mut(address as usize).ok_or(Fault)? = value;
+        Ok(())
+    }
+
+    fn reference_step(mut state: State, quirks: Quirks) -> Result<State, Fault> {
+        let pc = state.program_counter;
+        let opcode = u16::from_be_bytes([
+            read(&state.memory, pc)?,
+            read(&state.memory, pc.wrapping_add(1))?,
+        ]);
+        state.program_counter = pc.wrapping_add(2);
+
+        let x = (opcode >> 8 & 0xF) as usize;
+        let y = (opcode >> 4 & 0xF) as usize;
+        let nn = opcode as u8;
+        let nnn = opcode & 0xFFF;
+        let v = &mut state.v_registers;
+
+        match (opcode >> 12, opcode & 0xFF, opcode & 0xF) {
+            (0x0, _, _) if nnn == 0x0E0 => {}
+            (0x0, _, _) if nnn == 0x0EE => {
+                state.program_counter = state.stack.pop().ok_or(Fault)?
+            }
+            (0x0 | 0x1, _, _) => state.program_counter = nnn,
+            (0x2, _, _) => {
+                if state.stack.len() == STACK_DEPTH {
+                    return Err(Fault);
+                }
+                state.stack.push(state.program_counter);
+                state.program_counter = nnn;
+            }
+            (0x3, _, _) if v[x] == nn => state.program_counter += 2,
+            (0x4, _, _) if v[x] != nn => state.program_counter += 2,
+            (0x5, _, 0x0) if v[x] == v[y] => state.program_counter += 2,
+            (0x9, _, 0x0) if v[x] != v[y] => state.program_counter += 2,
+            (0x3 | 0x4, _, _) | (0x5 | 0x9, _, 0x0) => {}
+            (0x6, _, _) => v[x] = nn,
+            (0x7, _, _) => v[x] = v[x].wrapping_add(nn),
+            (0x8, _, 0x0) => v[x] = v[y],
+            (0x8, _, 0x1..=0x3) => {
+                v[x] = match opcode & 0xF {
+                    0x1 => v[x] | v[y],
+                    0x2 => v[x] & v[y],
+                    _ => v[x] ^ v[y],
+                };
+                if quirks.logic {
+                    v[0xF] = 0;
+                }
+            }
+            (0x8, _, 0x4) => {
+                let (sum, carry) = v[x].overflowing_add(v[y]);
+                v[x] = sum;
+                v[0xF] = carry as u8;
+            }
+            (0x8, _, 0x5) => {
+                let no_borrow = v[x] >= v[y];
+                v[x] = v[x].wrapping_sub(v[y]);
+                v[0xF] = no_borrow as u8;
+            }
+            (0x8, _, 0x7) => {
+                let no_borrow = v[y] >= v[x];
+                v[x] = v[y].wrapping_sub(v[x]);
+                v[0xF] = no_borrow as u8;
+            }
+            (0x8, _, 0x6 | 0xE) => {
+                let value = if quirks.shift { v[x] } else { v[y] };
+                if opcode & 0xF == 0x6 {
+                    v[x] = value >> 1;
+                    v[0xF] = value & 1;
+                } else {
+                    v[x] = value << 1;
+                    v[0xF] = value >> 7;
+                }
+            }
+            (0xA, _, _) => state.i_register = nnn,
+            (0xB, _, _) => {
+                let offset = if quirks.jump { v[x] } else { v[0] };
+                state.program_counter = nnn + offset as u16;
+            }
+            // No keys are pressed
+            (0xE, 0x9E, _) => {}
+            (0xE, 0xA1, _) => state.program_counter += 2,
+            (0xF, 0x07, _) => v[x] = state.delay_timer,
+            (0xF, 0x15, _) => state.delay_timer = v[x],
+            (0xF, 0x18, _) => state.sound_timer = v[x],
+            (0xF, 0x1E, _) => state.i_register = state.i_register.wrapping_add(v[x] as u16),
+            (0xF, 0x29, _) => {
+                state.i_register = FONT_ADDRESS + (v[x] & 0xF) as u16 * FONT_CHARACTER_SIZE
+            }
+            (0xF, 0x33, _) => {
+                let digits = [v[x] / 100, v[x] / 10 % 10, v[x] % 10];
+                for (offset, digit) in digits.into_iter().enumerate() {
+                    let address = state.i_register.wrapping_add(offset as u16);
+                    write(&mut state.memory, address, digit)?;
+                }
+            }
+            (0xF, 0x55 | 0x65, _) => {
+                for (offset, register) in v[..=x].iter_mut().enumerate() {
+                    let address = state.i_register.wrapping_add(offset as u16);
+                    if opcode & 0xFF == 0x55 {
+                        write(&mut state.memory, address, *register)?;
+                    } else {
+                        *register = read(&state.memory, address)?;
+                    }
+                }
+                if !quirks.memory_leave_i_unchanged {
+                    let increment = if quirks.memory_increment_by_x {
+                        x
+                    } else {
+                        x + 1
+                    };
+                    state.i_register = state.i_register.wrapping_add(increment as u16);
+                }
+            }
+            _ => return Err(Fault),
+        }
+        Ok(state)
+    }
+
+    fn emulator_step(state: &State, quirks: Quirks) -> Result<State, Fault> {
+        let mut emulator = Emulator::new(HeadlessPlatform::new(1));
+        emulator.set_quirks(quirks);
+        emulator
+            .bus_mut()
+            .load(
+                DATA_START_ADDRESS,
+                &state.memory[DATA_START_ADDRESS as usize..],
+            )
+            .unwrap();
+        emulator.program_counter = state.program_counter;
+        emulator.i_register = state.i_register;
+        emulator.v_registers = state.v_registers;
+        emulator.delay_timer = state.delay_timer;
+        emulator.sound_timer = state.sound_timer;
+        for &return_address in &state.stack {
+            emulator.call_stack.push(return_address).unwrap();
+        }
+
+        async_io::block_on(emulator.run_instruction_loop()).map_err(|_| Fault)?;
+
+        let mut stack: Vec<u16> = emulator.call_stack().backtrace().collect();
+        stack.reverse();
+        Ok(State {
+            program_counter: emulator.program_counter,
+            i_register: emulator.i_register,
+            v_registers: emulator.v_registers,
+            delay_timer: emulator.delay_timer,
+            sound_timer: emulator.sound_timer,
+            stack,
+            memory: emulator.bus().as_bytes().to_vec(),
+        })
+    }
+
+    fn interpreter_area() -> Vec<u8> {
+        let emulator = Emulator::new(HeadlessPlatform::new(1));
+        emulator.bus().as_bytes()[..DATA_START_ADDRESS as usize].to_vec()
+    }
+
+    // Fixed bits of each encoding, the rest are operands. Instructions that
+    // draw, wait for keys or read the random source depend on the platform and
+    // are covered by the emulator tests instead.
+    const ENCODINGS: [(u16, u16); 33] = [
+        (0xFFFF, 0x00E0),
+        (0xFFFF, 0x00EE),
+        (0xF000, 0x0000),
+        (0xF000, 0x1000),
+        (0xF000, 0x2000),
+        (0xF000, 0x3000),
+        (0xF000, 0x4000),
+        (0xF00F, 0x5000),
+        (0xF000, 0x6000),
+        (0xF000, 0x7000),
+        (0xF00F, 0x8000),
+        (0xF00F, 0x8001),
+        (0xF00F, 0x8002),
+        (0xF00F, 0x8003),
+        (0xF00F, 0x8004),
+        (0xF00F, 0x8005),
+        (0xF00F, 0x8006),
+        (0xF00F, 0x8007),
+        (0xF00F, 0x800E),
+        (0xF00F, 0x9000),
+        (0xF000, 0xA000),
+        (0xF000, 0xB000),
+        (0xF0FF, 0xE09E),
+        (0xF0FF, 0xE0A1),
+        (0xF0FF, 0xF007),
+        (0xF0FF, 0xF015),
+        (0xF0FF, 0xF018),
+        (0xF0FF, 0xF01E),
+        (0xF0FF, 0xF029),
+        (0xF0FF, 0xF033),
+        (0xF0FF, 0xF055),
+        (0xF0FF, 0xF065),
+        // Invalid
+        (0xF00F, 0x5001),
+    ];
+
+    fn opcode() -> impl Strategy<Value = u16> {
+        (0..ENCODINGS.len(), any::<u16>()).prop_map(|(encoding, operands)| {
+            let (mask, pattern) = ENCODINGS[encoding];
+            pattern | operands & !mask
+        })
+    }
+
+    fn quirks() -> impl Strategy<Value = Quirks> {
+        any::<[bool; 5]>().prop_map(
+            |[shift, memory_increment_by_x, memory_leave_i_unchanged, jump, logic]| Quirks {
+                shift,
+                memory_increment_by_x,
+                memory_leave_i_unchanged,
+                jump,
+                logic,
+                ..Quirks::default()
+            },
+        )
+    }
+
+    prop_compose! {
+        fn state()(
+            opcode in opcode(),
+            program_counter in (DATA_START_ADDRESS / 2..MEMORY_SIZE as u16 / 2).prop_map(|word| word * 2),
+            // Mostly inside memory so that memory instructions do not just fault,
+            // sometimes at the top of the address space so that FX1E wraps
+            i_register in prop_oneof![
+                4 => 0..MEMORY_SIZE as u16,
+                1 => any::<u16>(),
+                1 => 0xFF00..=u16::MAX,
+            ],
+            v_registers in any::<[u8; 16]>(),
+            delay_timer in any::<u8>(),
+            sound_timer in any::<u8>(),
+            stack in prop::collection::vec((0x100u16..0x800).prop_map(|word| word * 2), 0..=STACK_DEPTH),
+            // What FX65 can read, the rest of the program area is left empty
+            data in any::<[u8; 16]>(),
+        ) -> State {
+            let mut memory = interpreter_area();
+            memory.resize(MEMORY_SIZE, 0);
+            for (offset, value) in data.into_iter().enumerate() {
+                let address = i_register.wrapping_add(offset as u16) as usize;
+                if (DATA_START_ADDRESS as usize..MEMORY_SIZE).contains(&address) {
+                    memory[address] = value;
+                }
+            }
+            memory[program_counter as usize..program_counter as usize + 2]
+                .copy_from_slice(&opcode.to_be_bytes());
+            State {
+                program_counter,
+                i_register,
+                v_registers,
+                delay_timer,
+                sound_timer,
+                stack,
+                memory,
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2048))]
+
+        #[test]
+        fn instruction_matches_reference_model(state in state(), quirks in quirks()) {
+            let expected = reference_step(state.clone(), quirks);
+            let actual = emulator_step(&state, quirks);
+
+            prop_assert_eq!(actual, expected);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+// Behaviours that differ between CHIP-8 interpreters. Names follow the
+// chip-8-database quirk definitions, every quirk off is the behaviour most
+// modern CHIP-8 programs expect.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Quirks {
+    // 8XY6/8XYE shift VX in place instead of loading the shifted VY
+    pub shift: bool,
+    // FX55/FX65 increment I by X instead of X + 1
+    pub memory_increment_by_x: bool,
+    // FX55/FX65 leave I unchanged
+    pub memory_leave_i_unchanged: bool,
+    // DXYN wraps sprites around the edges instead of clipping them
+    pub wrap: bool,
+    // BNNN jumps to XNN + VX instead of NNN + V0
+    pub jump: bool,
+    // DXYN waits for the next 60 Hz frame before drawing
+    pub vblank: bool,
+    // 8XY1/8XY2/8XY3 reset VF to zero
+    pub logic: bool,
+}
+
+impl Quirks {
+    pub fn cosmac_vip() -> Self {
+        Quirks {
+            vblank: true,
+            logic: true,
+            ..Quirks::default()
+        }
+    }
+
+    pub fn superchip() -> Self {
+        Quirks {
+            shift: true,
+            memory_leave_i_unchanged: true,
+            jump: true,
+            ..Quirks::default()
+        }
+    }
+
+    pub fn xochip() -> Self {
+        Quirks {
+            wrap: true,
+            ..Quirks::default()
+        }
+    }
+
+    // Looks a quirk up by its chip-8-database name, e.g. "memoryIncrementByX"
+    pub fn by_name_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "shift" => Some(&mut self.shift),
+            "memoryIncrementByX" => Some(&mut self.memory_increment_by_x),
+            "memoryLeaveIUnchanged" => Some(&mut self.memory_leave_i_unchanged),
+            "wrap" => Some(&mut self.wrap),
+            "jump" => Some(&mut self.jump),
+            "vblank" => Some(&mut self.vblank),
+            "logic" => Some(&mut self.logic),
+            _ => None,
+        }
+    }
+}
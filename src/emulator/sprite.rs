@@ -0,0 +1,57 @@
+mod tests;
+
+use bitvec::prelude::*;
+
+use super::platform::{Pixel, PixelState, Platform};
+
+// XORs a sprite onto the display and reports whether any pixel was turned off.
+// The start position always wraps, the rest of the sprite wraps around the
+// edges or is clipped depending on `wrap`.
+pub async fn draw<PLATFORM: Platform>(
+    platform: &mut PLATFORM,
+    x: u8,
+    y: u8,
+    sprite: &[u8],
+    wrap: bool,
+) -> bool {
+    let display_width = platform.get_display_width().await as usize;
+    let display_height = platform.get_display_height().await as usize;
+    let starting_column = x as usize % display_width;
+    let starting_row = y as usize % display_height;
+
+    let mut collision = false;
+    for (row_offset, sprite_row) in sprite.iter().enumerate() {
+        let mut row = starting_row + row_offset;
+        if row >= display_height {
+            if !wrap {
+                break;
+            }
+            row %= display_height;
+        }
+
+        let set_bits = sprite_row.view_bits::<Msb0>().iter_ones();
+        for column_offset in set_bits {
+            let mut column = starting_column + column_offset;
+            if column >= display_width {
+                if !wrap {
+                    break;
+                }
+                column %= display_width;
+            }
+
+            let pixel = Pixel {
+                column: column as u8,
+                row: row as u8,
+            };
+            match platform.get_pixel(pixel).await {
+                PixelState::On => {
+                    platform.set_pixel(pixel, PixelState::Off).await;
+                    collision = true;
+                }
+                PixelState::Off => platform.set_pixel(pixel, PixelState::On).await,
+            }
+        }
+    }
+
+    collision
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod test {
+    use crate::emulator::framebuffer::Framebuffer;
+    use crate::emulator::platform::headless::HeadlessPlatform;
+    use crate::emulator::platform::{Pixel, PixelState};
+    use crate::emulator::sprite::*;
+
+    const ZERO: [u8; 5] = [0xF0, 0x90, 0x90, 0x90, 0xF0];
+
+    // Rows of '#' and '.' for the top left corner of the framebuffer
+    fn render(framebuffer: &Framebuffer, columns: u8, rows: u8) -> Vec<String> {
+        (0..rows)
+            .map(|row| {
+                (0..columns)
+                    .map(
+                        |column| match framebuffer.get_pixel(Pixel { column, row }) {
+                            PixelState::On => '#',
+                            PixelState::Off => '.',
+                        },
+                    )
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn lit_pixels(framebuffer: &Framebuffer) -> Vec<(u8, u8)> {
+        let mut pixels = vec![];
+        for row in 0..framebuffer.height() {
+            for column in 0..framebuffer.width() {
+                if framebuffer.get_pixel(Pixel { column, row }) == PixelState::On {
+                    pixels.push((column, row));
+                }
+            }
+        }
+        pixels
+    }
+
+    #[tokio::test]
+    async fn draws_sprite_at_position() {
+        let mut platform = HeadlessPlatform::new(1);
+
+        let collision = draw(&mut platform, 1, 1, &ZERO, false).await;
+
+        assert!(!collision);
+        let expected = [
+            "......", ".####.", ".#..#.", ".#..#.", ".#..#.", ".####.", "......",
+        ];
+        assert_eq!(render(platform.framebuffer(), 6, 7), expected);
+    }
+
+    #[tokio::test]
+    async fn drawing_twice_erases_and_collides() {
+        let mut platform = HeadlessPlatform::new(1);
+
+        draw(&mut platform, 3, 2, &ZERO, false).await;
+        let collision = draw(&mut platform, 3, 2, &ZERO, false).await;
+
+        assert!(collision);
+        assert!(lit_pixels(platform.framebuffer()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn collision_only_when_pixel_turns_off() {
+        let mut platform = HeadlessPlatform::new(1);
+        draw(&mut platform, 0, 0, &[0xF0], false).await;
+
+        // Touches the lit pixels without turning any off
+        let collision = draw(&mut platform, 4, 0, &[0xF0], false).await;
+        assert!(!collision);
+
+        // Overlaps a single lit pixel
+        let collision = draw(&mut platform, 7, 0, &[0x80], false).await;
+        assert!(collision);
+        assert_eq!(render(platform.framebuffer(), 9, 1), ["#######.."]);
+    }
+
+    #[tokio::test]
+    async fn start_position_wraps() {
+        let mut platform = HeadlessPlatform::new(1);
+
+        draw(&mut platform, 64 + 2, 32 + 1, &[0x80], false).await;
+
+        assert_eq!(lit_pixels(platform.framebuffer()), [(2, 1)]);
+    }
+
+    #[tokio::test]
+    async fn sprite_is_clipped_at_edges() {
+        let mut platform = HeadlessPlatform::new(1);
+
+        draw(&mut platform, 62, 30, &[0xFF, 0xFF, 0xFF], false).await;
+
+        assert_eq!(
+            lit_pixels(platform.framebuffer()),
+            [(62, 30), (63, 30), (62, 31), (63, 31)]
+        );
+    }
+
+    #[tokio::test]
+    async fn sprite_wraps_at_edges_with_wrap_quirk() {
+        let mut platform = HeadlessPlatform::new(1);
+
+        draw(&mut platform, 63, 31, &[0xC0, 0xC0], true).await;
+
+        assert_eq!(
+            lit_pixels(platform.framebuffer()),
+            [(0, 0), (63, 0), (0, 31), (63, 31)]
+        );
+    }
+}
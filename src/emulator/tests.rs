@@ -5,22 +5,32 @@ mod test {
         display: Display,
         buzzer: BuzzerState,
         keypad: Keypad,
-        waiters: Vec<futures::channel::oneshot::Sender<KeyState>>,
+        random_value: u8,
     }
 
-    use std::u8;
-
+    #[cfg(feature = "decode-cache")]
+    use super::super::bus::{Access, BusHook, MemoryBus};
+    use super::super::bus::{Bus, BusFault};
+    use super::super::call_stack::{StackDepth, StackFault};
+    use super::super::framebuffer::Framebuffer;
+    use super::super::platform::headless::HeadlessPlatform;
     use super::super::platform::*;
-    use super::super::Emulator;
+    use super::super::quirks::Quirks;
+    #[cfg(feature = "decode-cache")]
+    use super::super::CodeModification;
+    use super::super::{Emulator, Fault, FaultKind};
+    #[cfg(feature = "std")]
+    use crate::assert_screen_matches;
+    #[cfg(feature = "std")]
+    use crate::audio::{AudioRecorder, SquareWave};
 
-    const WIDTH: u8 = 32;
-    const HEIGHT: u8 = 64;
+    const WIDTH: u8 = 64;
+    const HEIGHT: u8 = 32;
     type Row = [PixelState; WIDTH as usize];
     type Display = [Row; HEIGHT as usize];
 
     type Keypad = [KeyState; KEYPAD_COUNT as usize];
 
-    #[async_trait::async_trait]
     impl Platform for TestPlatform {
         // Display
         async fn get_display_width(&self) -> u8 {
@@ -33,20 +43,13 @@ mod test {
             self.display.fill([PixelState::Off; WIDTH as usize])
         }
         async fn get_pixel(&self, pixel: Pixel) -> PixelState {
-            self.display[pixel.row as usize][pixel.row as usize]
+            self.display[pixel.row as usize][pixel.column as usize]
         }
         async fn set_pixel(&mut self, pixel: Pixel, state: PixelState) {
-            self.display[pixel.row as usize][pixel.row as usize] = state
+            self.display[pixel.row as usize][pixel.column as usize] = state
         }
 
         // Keypad
-        async fn block_for_any_keypress(&mut self) -> KeyState {
-            let (sender, receive) = futures::channel::oneshot::channel::<KeyState>();
-
-            self.waiters.push(sender);
-
-            receive.await.unwrap()
-        }
         async fn read_keypress_state(&self, key: KeypadNumber) -> KeyState {
             let index: usize = key.into();
             self.keypad[index]
@@ -56,6 +59,15 @@ mod test {
         async fn set_buzzer(&mut self, state: BuzzerState) {
             self.buzzer = state
         }
+
+        // Random
+        async fn random_byte(&mut self) -> u8 {
+            self.random_value = self.random_value.wrapping_add(1);
+            self.random_value
+        }
+
+        // Time
+        async fn wait_for_next_frame(&mut self) {}
     }
 
     impl TestPlatform {
@@ -64,18 +76,13 @@ mod test {
                 display: [[PixelState::Off; WIDTH as usize]; HEIGHT as usize],
                 buzzer: BuzzerState::Off,
                 keypad: [KeyState::Off; KEYPAD_COUNT as usize],
-                waiters: vec![],
+                random_value: 0,
             }
         }
 
         pub fn set_keypress(&mut self, key: KeypadNumber, key_state: KeyState) {
             let index: usize = key.into();
             self.keypad[index] = key_state;
-
-            for sender in self.waiters.drain(..) {
-                sender.send(key_state).unwrap();
-            }
-            assert!(self.waiters.len() == 0);
         }
 
         pub fn get_buzzer(&self) -> BuzzerState {
@@ -98,8 +105,550 @@ mod test {
             }
             let binary_data = f.contents();
             let mut emulator = new_test_emulator();
-            emulator.load_into_memory(binary_data).await;
-            emulator.start_program().await;
+            emulator.load_into_memory(binary_data).await.unwrap();
+            emulator.run_instruction_loop().await.unwrap();
+        }
+    }
+
+    // V0 = 6, sound timer = V0, then loop forever
+    const BEEP_ROM: [u8; 6] = [0x60, 0x06, 0xF0, 0x18, 0x12, 0x04];
+
+    #[tokio::test]
+    async fn sound_timer_drives_buzzer_at_60_hz() {
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(&BEEP_ROM).await.unwrap();
+
+        let mut buzzer_states = vec![];
+        for _ in 0..10 {
+            emulator.run_frame().await.unwrap();
+            buzzer_states.push(emulator.platform().get_buzzer());
+        }
+
+        // Set during the first frame, silenced in the frame after the sixth tick
+        let (on, off) = (BuzzerState::On, BuzzerState::Off);
+        assert_eq!(buzzer_states, [on, on, on, on, on, on, off, off, off, off]);
+    }
+
+    #[cfg(feature = "std")]
+    #[tokio::test]
+    async fn buzzer_records_a_beep_of_the_sound_timer_length() {
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(&BEEP_ROM).await.unwrap();
+
+        let wave = SquareWave::default().with_ramp_milliseconds(0.0);
+        let samples_per_frame = wave.samples_per_frame();
+        let mut recorder = AudioRecorder::new(wave);
+        for _ in 0..10 {
+            emulator.run_frame().await.unwrap();
+            recorder.record_frame(emulator.platform().get_buzzer());
+        }
+
+        let (beep, silence) = recorder.samples().split_at(6 * samples_per_frame);
+        assert!(beep.iter().all(|&sample| sample != 0.0));
+        assert!(silence.iter().all(|&sample| sample == 0.0));
+    }
+
+    #[tokio::test]
+    async fn jump_quirk_uses_register_from_address() {
+        // V0 = 0x10, V3 = 0x20, jump to 0x300 plus a register
+        let rom = [0x60, 0x10, 0x63, 0x20, 0xB3, 0x00];
+        for (quirks, expected_program_counter) in
+            [(Quirks::default(), 0x310), (Quirks::superchip(), 0x320)]
+        {
+            let mut emulator = new_test_emulator();
+            emulator.set_quirks(quirks);
+            emulator.load_into_memory(&rom).await.unwrap();
+
+            for _ in 0..3 {
+                emulator.run_instruction_loop().await.unwrap();
+            }
+
+            assert_eq!(emulator.program_counter, expected_program_counter);
+        }
+    }
+
+    #[tokio::test]
+    async fn run_cycles_ticks_timers_across_calls() {
+        // Delay timer = 10, then loop
+        let rom = [0x60, 0x0A, 0xF0, 0x15, 0x12, 0x04];
+        let mut emulator = new_test_emulator();
+        emulator.set_instructions_per_frame(4);
+        emulator.load_into_memory(&rom).await.unwrap();
+
+        assert_eq!(emulator.run_cycles(3).await, (3, Ok(())));
+        assert_eq!(emulator.delay_timer, 10);
+
+        // Ticks after cycles 4 and 8
+        assert_eq!(emulator.run_cycles(6).await, (6, Ok(())));
+        assert_eq!(emulator.delay_timer, 8);
+    }
+
+    #[tokio::test]
+    async fn run_cycles_counts_cycles_before_a_fault() {
+        // V0 = 1, V1 = 2, then return with an empty call stack
+        let rom = [0x60, 0x01, 0x61, 0x02, 0x00, 0xEE];
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(&rom).await.unwrap();
+
+        let fault = Fault {
+            address: 0x204,
+            kind: FaultKind::Stack(StackFault::Underflow),
+        };
+        assert_eq!(emulator.run_cycles(10).await, (2, Err(fault)));
+    }
+
+    #[test]
+    fn emulator_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Emulator<HeadlessPlatform>>();
+    }
+
+    #[test]
+    fn framebuffer_hash_is_stable() {
+        let mut framebuffer = Framebuffer::new();
+        assert_eq!(framebuffer.hash(), 0x28c31cf8df2ec325);
+
+        framebuffer.set_pixel(Pixel { column: 0, row: 0 }, PixelState::On);
+        assert_eq!(framebuffer.hash(), 0xda2a54478fa6a324);
+    }
+
+    #[tokio::test]
+    async fn vblank_quirk_draws_once_per_frame() {
+        // I = font "0", draw it twice at (V0, V0), then loop
+        let rom = [0xA0, 0x50, 0xD0, 0x05, 0xD0, 0x05, 0x12, 0x06];
+        let mut emulator = new_test_emulator();
+        emulator.set_quirks(Quirks::cosmac_vip());
+        emulator.load_into_memory(&rom).await.unwrap();
+        let top_left = Pixel { column: 0, row: 0 };
+
+        // Each draw waits for the end of the frame it is reached in
+        for (program_counter, pixel) in [
+            (0x202, PixelState::Off),
+            (0x204, PixelState::On),
+            (0x206, PixelState::Off),
+        ] {
+            emulator.run_frame().await.unwrap();
+            assert_eq!(emulator.program_counter, program_counter);
+            assert_eq!(emulator.platform().get_pixel(top_left).await, pixel);
+        }
+
+        // Without the quirk both draws happen in the first frame
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(&rom).await.unwrap();
+        emulator.run_frame().await.unwrap();
+        assert_eq!(emulator.program_counter, 0x206);
+    }
+
+    #[tokio::test]
+    async fn memory_quirks_control_i_register() {
+        // I = 0x300, store V0 to V2
+        let rom = [0xA3, 0x00, 0xF2, 0x55];
+        for (quirks, expected_i_register) in [
+            (Quirks::default(), 0x303),
+            (
+                Quirks {
+                    memory_increment_by_x: true,
+                    ..Quirks::default()
+                },
+                0x302,
+            ),
+            (Quirks::superchip(), 0x300),
+        ] {
+            let mut emulator = new_test_emulator();
+            emulator.set_quirks(quirks);
+            emulator.load_into_memory(&rom).await.unwrap();
+
+            for _ in 0..2 {
+                emulator.run_instruction_loop().await.unwrap();
+            }
+
+            assert_eq!(emulator.i_register, expected_i_register);
+        }
+    }
+
+    #[tokio::test]
+    async fn key_wait_stores_key_number_after_release() {
+        // Wait for a key into V3, then loop forever
+        let rom = [0xF3, 0x0A, 0x12, 0x02];
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(&rom).await.unwrap();
+
+        emulator.run_frame().await.unwrap();
+        assert_eq!(emulator.program_counter, 0x200);
+
+        let key = KeypadNumber(0xB);
+        emulator.platform_mut().set_keypress(key, KeyState::On);
+        emulator.run_frame().await.unwrap();
+        assert_eq!(emulator.program_counter, 0x200);
+        assert_eq!(emulator.v_registers[3], 0);
+
+        emulator.platform_mut().set_keypress(key, KeyState::Off);
+        emulator.run_frame().await.unwrap();
+        assert_eq!(emulator.program_counter, 0x202);
+        assert_eq!(emulator.v_registers[3], 0xB);
+    }
+
+    #[tokio::test]
+    async fn timers_tick_while_waiting_for_key() {
+        // Delay timer = 10, sound timer = 10, then wait for a key into V1
+        let rom = [0x60, 0x0A, 0xF0, 0x15, 0xF0, 0x18, 0xF1, 0x0A];
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(&rom).await.unwrap();
+
+        for _ in 0..4 {
+            emulator.run_frame().await.unwrap();
+        }
+
+        assert_eq!(emulator.program_counter, 0x206);
+        assert_eq!(emulator.delay_timer, 6);
+        assert_eq!(emulator.sound_timer, 6);
+    }
+
+    // Draws a 1 2 3 menu, picks an entry with FX0A and shows it:
+    //
+    //   0x200  CLS / LD V0, 1 / LD V1, 2 / LD V2, 2
+    //   0x208  LD F, V0 / DRW V1, V2, 5 / ADD V1, 6 / ADD V0, 1 / SE V0, 4 / JP 0x208
+    //   0x214  LD V3, K / SNE V3, 0 / JP 0x214
+    //   0x21A  LD V4, 3 / SUB V4, V3 / SE VF, 1 / JP 0x214
+    //   0x222  CLS / LD F, V3 / LD VA, 28 / LD VB, 13 / DRW VA, VB, 5
+    //   0x22C  JP 0x22C
+    static KEYPAD_MENU_ROM: &[u8] = include_bytes!("test-dependencies/keypad-menu.ch8");
+    const KEYPAD_MENU_WAIT: u16 = 0x214;
+    const KEYPAD_MENU_DONE: u16 = 0x22C;
+
+    // Runs a ROM to its FX0A menu and picks entry 3, which has to wait for the
+    // key to be released
+    async fn pick_menu_entry(rom: &[u8]) -> Emulator<TestPlatform> {
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(rom).await.unwrap();
+
+        for _ in 0..60 {
+            emulator.run_frame().await.unwrap();
+        }
+        let menu_program_counter = emulator.program_counter;
+        let pc = menu_program_counter;
+        assert_eq!(emulator.bus().peek(pc).unwrap() & 0xF0, 0xF0);
+        assert_eq!(emulator.bus().peek(pc + 1), Some(0x0A));
+
+        let key = KeypadNumber(0x3);
+        emulator.platform_mut().set_keypress(key, KeyState::On);
+        for _ in 0..10 {
+            emulator.run_frame().await.unwrap();
+        }
+        assert_eq!(emulator.program_counter, menu_program_counter);
+
+        emulator.platform_mut().set_keypress(key, KeyState::Off);
+        emulator.run_frame().await.unwrap();
+        assert_ne!(emulator.program_counter, menu_program_counter);
+        emulator
+    }
+
+    #[tokio::test]
+    async fn keypad_menu_waits_for_key_release() {
+        let mut emulator = pick_menu_entry(KEYPAD_MENU_ROM).await;
+        emulator.run_frame().await.unwrap();
+        assert_eq!(emulator.program_counter, KEYPAD_MENU_DONE);
+        assert_eq!(emulator.v_registers[3], 3);
+    }
+
+    #[tokio::test]
+    async fn keypad_menu_ignores_entries_it_does_not_have() {
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(KEYPAD_MENU_ROM).await.unwrap();
+        for _ in 0..10 {
+            emulator.run_frame().await.unwrap();
+        }
+
+        let key = KeypadNumber(0x7);
+        emulator.platform_mut().set_keypress(key, KeyState::On);
+        emulator.run_frame().await.unwrap();
+        emulator.platform_mut().set_keypress(key, KeyState::Off);
+        emulator.run_frame().await.unwrap();
+
+        assert_eq!(emulator.program_counter, KEYPAD_MENU_WAIT);
+        assert_eq!(emulator.v_registers[3], 7);
+    }
+
+    // Only runs anything with the chip8-test-suite submodule checked out
+    #[tokio::test]
+    async fn keypad_test_menu_waits_for_key_release() {
+        let keypad_tests = TEST_BINARY_DIR
+            .files()
+            .filter(|f| f.path().to_string_lossy().contains("keypad"));
+
+        for f in keypad_tests {
+            pick_menu_entry(f.contents()).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn alu_flag_is_written_after_result() {
+        // VF = 0xFF, V0 = 0x01, VF += V0, then V1 = 0x05, V1 -= VF
+        let rom = [0x6F, 0xFF, 0x60, 0x01, 0x8F, 0x04, 0x61, 0x05, 0x81, 0xF5];
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(&rom).await.unwrap();
+
+        for _ in 0..3 {
+            emulator.run_instruction_loop().await.unwrap();
+        }
+        // The carry overwrites the wrapped sum
+        assert_eq!(emulator.v_registers[0xF], 1);
+
+        for _ in 0..2 {
+            emulator.run_instruction_loop().await.unwrap();
+        }
+        // VF is read as an operand before it is replaced by the borrow flag
+        assert_eq!(emulator.v_registers[1], 4);
+        assert_eq!(emulator.v_registers[0xF], 1);
+    }
+
+    #[tokio::test]
+    async fn draw_sets_vf_on_collision() {
+        // I = 0x20A, V0 = 0x05, draw at (V0, V0) twice, then loop forever
+        let rom = [
+            0xA2, 0x0A, 0x60, 0x05, 0xD0, 0x01, 0xD0, 0x01, 0x12, 0x08, 0x80,
+        ];
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(&rom).await.unwrap();
+        let pixel = Pixel { column: 5, row: 5 };
+
+        for _ in 0..3 {
+            emulator.run_instruction_loop().await.unwrap();
+        }
+        assert_eq!(emulator.v_registers[0xF], 0);
+        assert_eq!(emulator.platform.get_pixel(pixel).await, PixelState::On);
+
+        emulator.run_instruction_loop().await.unwrap();
+        assert_eq!(emulator.v_registers[0xF], 1);
+        assert_eq!(emulator.platform.get_pixel(pixel).await, PixelState::Off);
+    }
+
+    #[tokio::test]
+    async fn call_faults_past_configured_depth() {
+        // Calls 0x200 recursively until the stack is full
+        let rom = [0x22, 0x00];
+        let mut emulator = new_test_emulator();
+        emulator.set_stack_depth(StackDepth::COSMAC_VIP).unwrap();
+        emulator.load_into_memory(&rom).await.unwrap();
+
+        for _ in 0..12 {
+            emulator.run_instruction_loop().await.unwrap();
+        }
+        assert_eq!(emulator.call_stack().len(), 12);
+
+        assert_eq!(
+            emulator.run_instruction_loop().await,
+            Err(Fault {
+                address: 0x200,
+                kind: StackFault::Overflow { depth: 12 }.into()
+            })
+        );
+        let backtrace: Vec<u16> = emulator.call_stack().backtrace().collect();
+        assert_eq!(backtrace, [0x202; 12]);
+    }
+
+    #[test]
+    fn stack_depth_past_the_maximum_is_an_error() {
+        let mut emulator = new_test_emulator();
+
+        assert_eq!(
+            emulator.set_stack_depth(StackDepth::Limited(65)),
+            Err(StackFault::DepthTooLarge { depth: 65 })
+        );
+        assert_eq!(emulator.call_stack().depth(), StackDepth::default());
+    }
+
+    #[tokio::test]
+    async fn return_goes_back_to_caller() {
+        // Call 0x206, which returns to 0x202 and loops there
+        let rom = [0x22, 0x06, 0x12, 0x02, 0x00, 0x00, 0x00, 0xEE];
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(&rom).await.unwrap();
+
+        for _ in 0..3 {
+            emulator.run_instruction_loop().await.unwrap();
+        }
+
+        assert_eq!(emulator.program_counter, 0x202);
+        assert!(emulator.call_stack().is_empty());
+    }
+
+    #[tokio::test]
+    async fn return_with_empty_stack_faults() {
+        let rom = [0x00, 0xEE];
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(&rom).await.unwrap();
+
+        assert_eq!(
+            emulator.run_instruction_loop().await,
+            Err(Fault {
+                address: 0x200,
+                kind: StackFault::Underflow.into()
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn font_sprite_address_for_digit() {
+        // VA = 0x0B, I = sprite for VA, load the first row into V0
+        let rom = [0x6A, 0x0B, 0xFA, 0x29, 0xF0, 0x65];
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(&rom).await.unwrap();
+
+        for _ in 0..2 {
+            emulator.run_instruction_loop().await.unwrap();
+        }
+        assert_eq!(emulator.i_register, 0x050 + 0xB * 5);
+
+        emulator.run_instruction_loop().await.unwrap();
+        assert_eq!(emulator.v_registers[0], 0xE0);
+    }
+
+    #[tokio::test]
+    async fn store_into_interpreter_area_faults() {
+        // I = 0x100, store V0
+        let rom = [0xA1, 0x00, 0xF0, 0x55];
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(&rom).await.unwrap();
+
+        emulator.run_instruction_loop().await.unwrap();
+
+        assert_eq!(
+            emulator.run_instruction_loop().await,
+            Err(Fault {
+                address: 0x202,
+                kind: FaultKind::Bus(BusFault::ReadOnly { address: 0x100 })
+            })
+        );
+    }
+
+    #[cfg(feature = "decode-cache")]
+    #[tokio::test]
+    async fn overwritten_code_is_redecoded_and_reported() {
+        // Call the return at 0x20A, then store V0 and V1 over it so that it
+        // becomes a jump to 0x20E, and jump back to it
+        let rom = [
+            0xA2, 0x0A, 0x60, 0x12, 0x61, 0x0E, 0x22, 0x0A, 0xF1, 0x55, 0x00, 0xEE, 0x12, 0x0A,
+            0x12, 0x0E,
+        ];
+        // Borrowing the bus drops decoded instructions but not which ones ran
+        for borrow_bus in [false, true] {
+            let mut emulator = new_test_emulator();
+            emulator.load_into_memory(&rom).await.unwrap();
+
+            for _ in 0..5 {
+                emulator.run_instruction_loop().await.unwrap();
+            }
+            if borrow_bus {
+                emulator.bus_mut();
+            }
+            for _ in 0..4 {
+                emulator.run_instruction_loop().await.unwrap();
+            }
+
+            assert_eq!(emulator.program_counter, 0x20E);
+            let modifications: Vec<_> = emulator.code_modifications().collect();
+            assert_eq!(
+                modifications,
+                [(
+                    0x20A,
+                    CodeModification {
+                        written_by: 0x208,
+                        writes: 1
+                    }
+                )]
+            );
         }
     }
+
+    #[cfg(feature = "decode-cache")]
+    #[derive(Default)]
+    struct ExecuteCount(u32);
+
+    #[cfg(feature = "decode-cache")]
+    impl BusHook for ExecuteCount {
+        fn on_access(&mut self, _address: u16, _value: u8, access: Access) {
+            if access == Access::Execute {
+                self.0 += 1;
+            }
+        }
+    }
+
+    #[cfg(feature = "decode-cache")]
+    #[tokio::test]
+    async fn decoded_instructions_are_not_fetched_again() {
+        // Jump to 0x200 forever
+        let bus = MemoryBus::standard().with_hook(ExecuteCount::default());
+        let mut emulator = Emulator::with_bus(TestPlatform::new(), bus);
+        emulator.load_into_memory(&[0x12, 0x00]).await.unwrap();
+
+        for _ in 0..10 {
+            emulator.run_instruction_loop().await.unwrap();
+        }
+
+        // Both bytes of the jump, once
+        assert_eq!(emulator.bus().hook().0, 2);
+    }
+
+    #[tokio::test]
+    async fn invalid_opcode_faults() {
+        let rom = [0x51, 0x21];
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(&rom).await.unwrap();
+
+        assert_eq!(
+            emulator.run_instruction_loop().await,
+            Err(Fault {
+                address: 0x200,
+                kind: FaultKind::InvalidOpcode { opcode: 0x5121 }
+            })
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[tokio::test]
+    async fn font_sprites_match_golden() {
+        // Draw the 16 font sprites side by side along row 1
+        let rom = [
+            0x60, 0x00, 0x61, 0x00, 0x62, 0x01, 0xF0, 0x29, 0xD1, 0x25, 0x71, 0x04, 0x70, 0x01,
+            0x40, 0x10, 0x12, 0x14, 0x12, 0x06, 0x12, 0x14,
+        ];
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(&rom).await.unwrap();
+        assert_eq!(emulator.run_cycles(100).await, (100, Ok(())));
+
+        assert_screen_matches!(emulator, "golden/font.pbm");
+    }
+
+    #[cfg(feature = "std")]
+    #[tokio::test]
+    async fn wrap_quirk_matches_golden() {
+        // I = font "8", draw it at (62, 30), then loop
+        let rom = [0xA0, 0x78, 0x60, 0x3E, 0x61, 0x1E, 0xD0, 0x15, 0x12, 0x08];
+        for (wrap, golden) in [(false, "golden/clipped.pbm"), (true, "golden/wrapped.pbm")] {
+            let mut emulator = new_test_emulator();
+            emulator.set_quirks(Quirks {
+                wrap,
+                ..Quirks::default()
+            });
+            emulator.load_into_memory(&rom).await.unwrap();
+            assert_eq!(emulator.run_cycles(10).await, (10, Ok(())));
+
+            assert_screen_matches!(emulator, golden);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[tokio::test]
+    async fn clear_screen_matches_golden() {
+        // Draw font "0", clear the screen, then loop
+        let rom = [0xA0, 0x50, 0xD0, 0x05, 0x00, 0xE0, 0x12, 0x06];
+        let mut emulator = new_test_emulator();
+        emulator.load_into_memory(&rom).await.unwrap();
+
+        assert_eq!(emulator.run_cycles(2).await, (2, Ok(())));
+        assert_screen_matches!(emulator, "golden/zero.pbm");
+        assert_eq!(emulator.run_cycles(1).await, (1, Ok(())));
+        assert_screen_matches!(emulator, "golden/empty.pbm");
+    }
 }
@@ -0,0 +1,323 @@
+// Controllers through Linux evdev. Events are the raw struct input_event
+// records read from /dev/input/event*, or from a recording of one made with
+// `cat /dev/input/eventN > recording`.
+mod tests;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Read};
+#[cfg(target_os = "linux")]
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::emulator::platform::{KeyState, KeypadNumber};
+
+// From linux/input-event-codes.h
+pub const EV_SYN: u16 = 0x00;
+pub const EV_KEY: u16 = 0x01;
+pub const EV_ABS: u16 = 0x03;
+
+pub const BTN_SOUTH: u16 = 0x130;
+pub const BTN_EAST: u16 = 0x131;
+pub const BTN_NORTH: u16 = 0x133;
+pub const BTN_WEST: u16 = 0x134;
+pub const BTN_TL: u16 = 0x136;
+pub const BTN_TR: u16 = 0x137;
+pub const BTN_SELECT: u16 = 0x13A;
+pub const BTN_START: u16 = 0x13B;
+pub const BTN_DPAD_UP: u16 = 0x220;
+pub const BTN_DPAD_DOWN: u16 = 0x221;
+pub const BTN_DPAD_LEFT: u16 = 0x222;
+pub const BTN_DPAD_RIGHT: u16 = 0x223;
+
+pub const ABS_HAT0X: u16 = 0x10;
+pub const ABS_HAT0Y: u16 = 0x11;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct InputEvent {
+    // Since the epoch for devices
+    pub time: Duration,
+    pub kind: u16,
+    pub code: u16,
+    pub value: i32,
+}
+
+impl InputEvent {
+    // A struct timeval of two longs, then type, code and value
+    const LONG_SIZE: usize = core::mem::size_of::<usize>();
+    const TIME_SIZE: usize = 2 * Self::LONG_SIZE;
+    pub const SIZE: usize = Self::TIME_SIZE + 8;
+
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        let long = |index: usize| {
+            let start = index * Self::LONG_SIZE;
+            let mut long = [0; core::mem::size_of::<usize>()];
+            long.copy_from_slice(&bytes[start..start + Self::LONG_SIZE]);
+            usize::from_ne_bytes(long) as u64
+        };
+        let field = &bytes[Self::TIME_SIZE..];
+        InputEvent {
+            time: Duration::from_secs(long(0)) + Duration::from_micros(long(1)),
+            kind: u16::from_ne_bytes([field[0], field[1]]),
+            code: u16::from_ne_bytes([field[2], field[3]]),
+            value: i32::from_ne_bytes([field[4], field[5], field[6], field[7]]),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        let seconds = self.time.as_secs() as usize;
+        let micros = self.time.subsec_micros() as usize;
+        bytes[..Self::LONG_SIZE].copy_from_slice(&seconds.to_ne_bytes());
+        bytes[Self::LONG_SIZE..Self::TIME_SIZE].copy_from_slice(&micros.to_ne_bytes());
+        let field = &mut bytes[Self::TIME_SIZE..];
+        field[0..2].copy_from_slice(&self.kind.to_ne_bytes());
+        field[2..4].copy_from_slice(&self.code.to_ne_bytes());
+        field[4..8].copy_from_slice(&self.value.to_ne_bytes());
+        bytes
+    }
+}
+
+// Blocks on devices until the next event, ends at the end of a recording
+pub struct EventReader<R: Read> {
+    reader: R,
+}
+
+impl<R: Read> EventReader<R> {
+    pub fn new(reader: R) -> Self {
+        EventReader { reader }
+    }
+}
+
+impl<R: Read> Iterator for EventReader<R> {
+    type Item = io::Result<InputEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut bytes = [0; InputEvent::SIZE];
+        match self.reader.read_exact(&mut bytes) {
+            Ok(()) => Some(Ok(InputEvent::from_bytes(&bytes))),
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => None,
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
+// Event devices with gamepad or joystick buttons, from their sysfs capabilities
+#[cfg(target_os = "linux")]
+pub fn find_gamepads() -> io::Result<Vec<PathBuf>> {
+    let mut gamepads = Vec::new();
+    for entry in std::fs::read_dir("/sys/class/input")? {
+        let name = entry?.file_name();
+        let Some(name) = name.to_str().filter(|name| name.starts_with("event")) else {
+            continue;
+        };
+        let capabilities = format!("/sys/class/input/{name}/device/capabilities/key");
+        let Ok(capabilities) = std::fs::read_to_string(capabilities) else {
+            continue;
+        };
+        // BTN_JOYSTICK starts at 0x120, BTN_GAMEPAD at 0x130
+        if has_key(&capabilities, 0x120) || has_key(&capabilities, BTN_SOUTH) {
+            gamepads.push(PathBuf::from(format!("/dev/input/{name}")));
+        }
+    }
+    gamepads.sort();
+    Ok(gamepads)
+}
+
+// The sysfs bitmap is hex words, most significant first
+fn has_key(capabilities: &str, code: u16) -> bool {
+    let word_bits = usize::BITS as usize;
+    let words: Vec<&str> = capabilities.split_whitespace().rev().collect();
+    let code = code as usize;
+    words
+        .get(code / word_bits)
+        .and_then(|word| usize::from_str_radix(word, 16).ok())
+        .is_some_and(|word| word & (1 << (code % word_bits)) != 0)
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Control {
+    Up,
+    Down,
+    Left,
+    Right,
+    A,
+    B,
+    X,
+    Y,
+    L,
+    R,
+    Select,
+    Start,
+}
+
+impl Control {
+    pub const ALL: [Control; 12] = [
+        Control::Up,
+        Control::Down,
+        Control::Left,
+        Control::Right,
+        Control::A,
+        Control::B,
+        Control::X,
+        Control::Y,
+        Control::L,
+        Control::R,
+        Control::Select,
+        Control::Start,
+    ];
+
+    // Lowercase names, up to "b" they match chip-8-database key names
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|control| control.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Control::Up => "up",
+            Control::Down => "down",
+            Control::Left => "left",
+            Control::Right => "right",
+            Control::A => "a",
+            Control::B => "b",
+            Control::X => "x",
+            Control::Y => "y",
+            Control::L => "l",
+            Control::R => "r",
+            Control::Select => "select",
+            Control::Start => "start",
+        }
+    }
+
+    fn from_button(code: u16) -> Option<Self> {
+        match code {
+            BTN_DPAD_UP => Some(Control::Up),
+            BTN_DPAD_DOWN => Some(Control::Down),
+            BTN_DPAD_LEFT => Some(Control::Left),
+            BTN_DPAD_RIGHT => Some(Control::Right),
+            BTN_SOUTH => Some(Control::A),
+            BTN_EAST => Some(Control::B),
+            BTN_NORTH => Some(Control::X),
+            BTN_WEST => Some(Control::Y),
+            BTN_TL => Some(Control::L),
+            BTN_TR => Some(Control::R),
+            BTN_SELECT => Some(Control::Select),
+            BTN_START => Some(Control::Start),
+            _ => None,
+        }
+    }
+}
+
+// Controls to keypad keys, the default puts the D-pad on 5, 7, 8 and 9
+#[derive(Debug, PartialEq, Clone)]
+pub struct Profile {
+    bindings: BTreeMap<Control, KeypadNumber>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::empty()
+            .with_binding(Control::Up, KeypadNumber(0x5))
+            .with_binding(Control::Down, KeypadNumber(0x8))
+            .with_binding(Control::Left, KeypadNumber(0x7))
+            .with_binding(Control::Right, KeypadNumber(0x9))
+            .with_binding(Control::A, KeypadNumber(0x6))
+            .with_binding(Control::B, KeypadNumber(0x4))
+    }
+}
+
+impl Profile {
+    pub fn empty() -> Self {
+        Profile {
+            bindings: BTreeMap::new(),
+        }
+    }
+
+    // From a ROM database entry's keys, names that are not controls are skipped
+    pub fn from_named_keys(keys: &BTreeMap<String, KeypadNumber>) -> Self {
+        let mut profile = Self::empty();
+        for (name, number) in keys {
+            if let Some(control) = Control::from_name(name) {
+                profile.bind(control, *number);
+            }
+        }
+        profile
+    }
+
+    pub fn with_binding(mut self, control: Control, number: KeypadNumber) -> Self {
+        self.bind(control, number);
+        self
+    }
+
+    pub fn bind(&mut self, control: Control, number: KeypadNumber) {
+        self.bindings.insert(control, number);
+    }
+
+    pub fn keypad_number(&self, control: Control) -> Option<KeypadNumber> {
+        self.bindings.get(&control).copied()
+    }
+}
+
+// Held controls, updated from events
+#[derive(Debug, Clone, Default)]
+pub struct Gamepad {
+    profile: Profile,
+    held: BTreeSet<Control>,
+}
+
+impl Gamepad {
+    pub fn new(profile: Profile) -> Self {
+        Gamepad {
+            profile,
+            held: BTreeSet::new(),
+        }
+    }
+
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    pub fn handle_event(&mut self, event: InputEvent) {
+        match (event.kind, event.code) {
+            (EV_KEY, code) => {
+                if let Some(control) = Control::from_button(code) {
+                    // 2 is autorepeat
+                    self.set_held(control, event.value != 0);
+                }
+            }
+            (EV_ABS, ABS_HAT0X) => {
+                self.set_held(Control::Left, event.value < 0);
+                self.set_held(Control::Right, event.value > 0);
+            }
+            (EV_ABS, ABS_HAT0Y) => {
+                self.set_held(Control::Up, event.value < 0);
+                self.set_held(Control::Down, event.value > 0);
+            }
+            _ => {}
+        }
+    }
+
+    fn set_held(&mut self, control: Control, held: bool) {
+        if held {
+            self.held.insert(control);
+        } else {
+            self.held.remove(&control);
+        }
+    }
+
+    pub fn is_held(&self, control: Control) -> bool {
+        self.held.contains(&control)
+    }
+
+    // On while any control bound to it is held
+    pub fn state(&self, number: KeypadNumber) -> KeyState {
+        let held = self
+            .held
+            .iter()
+            .any(|control| self.profile.keypad_number(*control) == Some(number));
+        if held {
+            KeyState::On
+        } else {
+            KeyState::Off
+        }
+    }
+}
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod test {
+    use crate::gamepad::*;
+
+    fn event(kind: u16, code: u16, value: i32) -> InputEvent {
+        InputEvent {
+            time: Duration::ZERO,
+            kind,
+            code,
+            value,
+        }
+    }
+
+    fn recording(events: &[InputEvent]) -> Vec<u8> {
+        events.iter().flat_map(InputEvent::to_bytes).collect()
+    }
+
+    #[test]
+    fn reads_recorded_event_stream() {
+        let events = [
+            InputEvent {
+                time: Duration::new(1_700_000_000, 250_000_000),
+                ..event(EV_KEY, BTN_SOUTH, 1)
+            },
+            event(EV_SYN, 0, 0),
+            event(EV_ABS, ABS_HAT0X, -1),
+        ];
+        let bytes = recording(&events);
+
+        let read: Vec<InputEvent> = EventReader::new(bytes.as_slice())
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(read, events);
+
+        // A truncated record ends the stream
+        let read = EventReader::new(&bytes[..InputEvent::SIZE + 3]);
+        assert_eq!(read.count(), 1);
+    }
+
+    #[test]
+    fn buttons_and_hat_drive_profile_keys() {
+        let mut gamepad =
+            Gamepad::new(Profile::default().with_binding(Control::Start, KeypadNumber(0x6)));
+        let bytes = recording(&[
+            event(EV_ABS, ABS_HAT0Y, -1),
+            event(EV_KEY, BTN_START, 1),
+            event(EV_KEY, BTN_SOUTH, 1),
+            event(EV_KEY, BTN_SOUTH, 0),
+            event(EV_SYN, 0, 0),
+        ]);
+        for event in EventReader::new(bytes.as_slice()) {
+            gamepad.handle_event(event.unwrap());
+        }
+
+        assert_eq!(gamepad.state(KeypadNumber(0x5)), KeyState::On);
+        assert_eq!(gamepad.state(KeypadNumber(0x6)), KeyState::On);
+        assert_eq!(gamepad.state(KeypadNumber(0x8)), KeyState::Off);
+
+        gamepad.handle_event(event(EV_ABS, ABS_HAT0Y, 1));
+        gamepad.handle_event(event(EV_KEY, BTN_START, 0));
+        assert_eq!(gamepad.state(KeypadNumber(0x5)), KeyState::Off);
+        assert_eq!(gamepad.state(KeypadNumber(0x8)), KeyState::On);
+        assert_eq!(gamepad.state(KeypadNumber(0x6)), KeyState::Off);
+    }
+
+    #[test]
+    fn profile_from_database_keys() {
+        let keys = BTreeMap::from([
+            ("up".to_string(), KeypadNumber(0x2)),
+            ("a".to_string(), KeypadNumber(0xA)),
+            ("player2Up".to_string(), KeypadNumber(0xC)),
+        ]);
+        let profile = Profile::from_named_keys(&keys);
+
+        assert_eq!(profile.keypad_number(Control::Up), Some(KeypadNumber(0x2)));
+        assert_eq!(profile.keypad_number(Control::A), Some(KeypadNumber(0xA)));
+        assert_eq!(profile.keypad_number(Control::Down), None);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn capability_bitmap_lookup() {
+        // 64 bit words, BTN_SOUTH is bit 0x130 = word 4, bit 48
+        let capabilities = "1 0 0 0 1000000000000 0 0 0 0\n";
+        assert!(has_key(capabilities, BTN_SOUTH));
+        assert!(!has_key(capabilities, BTN_EAST));
+        assert!(has_key(capabilities, 0x200));
+    }
+}
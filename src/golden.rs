@@ -0,0 +1,316 @@
+// Golden screens for tests. Goldens are PBM files, plain text so that they
+// diff well in review, or PNG screenshots at any whole scale. In PBM 1 is an
+// on pixel, so viewers show the screen as black on white.
+//
+// Run the tests with UPDATE_GOLDEN=1 to write missing or mismatched goldens
+// instead of failing.
+mod tests;
+
+use std::path::{Path, PathBuf};
+
+use derive_more::From;
+
+use crate::capture;
+use crate::emulator::framebuffer::{Framebuffer, DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use crate::emulator::platform::{Pixel, PixelState, Platform};
+use crate::render::{ColorPalette, Image, Renderer};
+
+pub const UPDATE_VARIABLE: &str = "UPDATE_GOLDEN";
+
+const DIFF_MATCH_OFF: [u8; 4] = [0x00, 0x00, 0x00, 0xff];
+const DIFF_MATCH_ON: [u8; 4] = [0x60, 0x60, 0x60, 0xff];
+// On in the screen but not the golden
+const DIFF_EXTRA: [u8; 4] = [0xff, 0x30, 0x30, 0xff];
+// On in the golden but not the screen
+const DIFF_MISSING: [u8; 4] = [0x30, 0x90, 0xff, 0xff];
+
+#[derive(Debug, From)]
+pub enum GoldenError {
+    Io(std::io::Error),
+    PngDecoding(png::DecodingError),
+    PngEncoding(png::EncodingError),
+    #[from(skip)]
+    Invalid(String),
+}
+
+impl std::fmt::Display for GoldenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GoldenError::Io(error) => write!(f, "{error}"),
+            GoldenError::PngDecoding(error) => write!(f, "invalid PNG: {error}"),
+            GoldenError::PngEncoding(error) => write!(f, "could not write PNG: {error}"),
+            GoldenError::Invalid(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for GoldenError {}
+
+fn pixels() -> impl Iterator<Item = Pixel> {
+    (0..DISPLAY_HEIGHT).flat_map(|row| (0..DISPLAY_WIDTH).map(move |column| Pixel { column, row }))
+}
+
+// Plain PBM, one text row per display row
+pub fn to_pbm(framebuffer: &Framebuffer) -> String {
+    let mut pbm = format!("P1\n{DISPLAY_WIDTH} {DISPLAY_HEIGHT}\n");
+    for row in 0..DISPLAY_HEIGHT {
+        let bits: Vec<&str> = (0..DISPLAY_WIDTH)
+            .map(
+                |column| match framebuffer.get_pixel(Pixel { column, row }) {
+                    PixelState::On => "1",
+                    PixelState::Off => "0",
+                },
+            )
+            .collect();
+        pbm.push_str(&bits.join(" "));
+        pbm.push('\n');
+    }
+    pbm
+}
+
+// Plain (P1) or raw (P4) PBM of the display size
+pub fn from_pbm(data: &[u8]) -> Result<Framebuffer, GoldenError> {
+    let invalid = |message: &str| GoldenError::Invalid(format!("invalid PBM: {message}"));
+
+    // The header is whitespace separated with # comments to the end of a line
+    let mut position = 0;
+    let mut next_token = || {
+        loop {
+            match data.get(position) {
+                Some(b'#') => {
+                    while data.get(position).is_some_and(|&byte| byte != b'\n') {
+                        position += 1;
+                    }
+                }
+                Some(byte) if byte.is_ascii_whitespace() => position += 1,
+                _ => break,
+            }
+        }
+        let start = position;
+        while data
+            .get(position)
+            .is_some_and(|byte| !byte.is_ascii_whitespace())
+        {
+            position += 1;
+        }
+        std::str::from_utf8(&data[start..position]).unwrap_or("")
+    };
+
+    let magic = next_token();
+    if magic != "P1" && magic != "P4" {
+        return Err(invalid("not a PBM"));
+    }
+    let size = (next_token().parse::<u8>(), next_token().parse::<u8>());
+    if size != (Ok(DISPLAY_WIDTH), Ok(DISPLAY_HEIGHT)) {
+        return Err(invalid(&format!(
+            "expected a {DISPLAY_WIDTH}x{DISPLAY_HEIGHT} image"
+        )));
+    }
+
+    let mut framebuffer = Framebuffer::new();
+    if magic == "P1" {
+        // Bits may or may not be separated by whitespace
+        let mut bits = data[position..]
+            .iter()
+            .filter(|byte| !byte.is_ascii_whitespace());
+        for pixel in pixels() {
+            let state = match bits.next() {
+                Some(b'1') => PixelState::On,
+                Some(b'0') => PixelState::Off,
+                Some(_) => return Err(invalid("bits must be 0 or 1")),
+                None => return Err(invalid("too few pixels")),
+            };
+            framebuffer.set_pixel(pixel, state);
+        }
+    } else {
+        // A single whitespace byte, then rows padded to whole bytes
+        let raster = data.get(position + 1..).unwrap_or_default();
+        let row_bytes = DISPLAY_WIDTH.div_ceil(8) as usize;
+        if raster.len() < row_bytes * DISPLAY_HEIGHT as usize {
+            return Err(invalid("too few pixels"));
+        }
+        for pixel in pixels() {
+            let byte = raster[pixel.row as usize * row_bytes + pixel.column as usize / 8];
+            if byte & (0x80 >> (pixel.column % 8)) != 0 {
+                framebuffer.set_pixel(pixel, PixelState::On);
+            }
+        }
+    }
+    Ok(framebuffer)
+}
+
+// A pixel is on if it is brighter than mid grey, so screenshots need a dark
+// background
+pub fn from_png(data: &[u8]) -> Result<Framebuffer, GoldenError> {
+    let mut decoder = png::Decoder::new(std::io::Cursor::new(data));
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size().unwrap_or_default()];
+    let info = reader.next_frame(&mut buffer)?;
+
+    let (width, height) = (info.width as usize, info.height as usize);
+    let scale = width / DISPLAY_WIDTH as usize;
+    if scale == 0
+        || width != scale * DISPLAY_WIDTH as usize
+        || height != scale * DISPLAY_HEIGHT as usize
+    {
+        return Err(GoldenError::Invalid(format!(
+            "invalid PNG: {width}x{height} is not a whole scale of the display"
+        )));
+    }
+
+    let channels = info.color_type.samples();
+    let mut framebuffer = Framebuffer::new();
+    for pixel in pixels() {
+        let x = pixel.column as usize * scale;
+        let y = pixel.row as usize * scale;
+        let offset = (y * width + x) * channels;
+        // Alpha, if any, is ignored
+        let brightness = match channels {
+            1 | 2 => buffer[offset] as u32,
+            _ => {
+                (buffer[offset] as u32 + buffer[offset + 1] as u32 + buffer[offset + 2] as u32) / 3
+            }
+        };
+        if brightness > 0x80 {
+            framebuffer.set_pixel(pixel, PixelState::On);
+        }
+    }
+    Ok(framebuffer)
+}
+
+fn is_png(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("png"))
+}
+
+// PBM unless the path ends in .png
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Framebuffer, GoldenError> {
+    let path = path.as_ref();
+    let data = std::fs::read(path)?;
+    if is_png(path) {
+        from_png(&data)
+    } else {
+        from_pbm(&data)
+    }
+}
+
+// PBM unless the path ends in .png, which is saved unscaled in black and white
+pub fn save<P: AsRef<Path>>(framebuffer: &Framebuffer, path: P) -> Result<(), GoldenError> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if is_png(path) {
+        let image = Renderer::new(ColorPalette::default()).render(framebuffer);
+        capture::save_png(&image, path)?;
+    } else {
+        std::fs::write(path, to_pbm(framebuffer))?;
+    }
+    Ok(())
+}
+
+pub fn mismatches(screen: &Framebuffer, golden: &Framebuffer) -> Vec<Pixel> {
+    pixels()
+        .filter(|&pixel| screen.get_pixel(pixel) != golden.get_pixel(pixel))
+        .collect()
+}
+
+// Matching pixels in black and grey, pixels only on in the screen in red and
+// pixels only on in the golden in blue
+pub fn diff_image(screen: &Framebuffer, golden: &Framebuffer) -> Image {
+    let rgba = pixels()
+        .flat_map(
+            |pixel| match (screen.get_pixel(pixel), golden.get_pixel(pixel)) {
+                (PixelState::Off, PixelState::Off) => DIFF_MATCH_OFF,
+                (PixelState::On, PixelState::On) => DIFF_MATCH_ON,
+                (PixelState::On, PixelState::Off) => DIFF_EXTRA,
+                (PixelState::Off, PixelState::On) => DIFF_MISSING,
+            },
+        )
+        .collect();
+    Image {
+        width: DISPLAY_WIDTH as usize,
+        height: DISPLAY_HEIGHT as usize,
+        rgba,
+    }
+}
+
+// Next to the golden, e.g. golden/logo.pbm gives golden/logo.diff.png
+fn sibling(golden: &Path, suffix: &str) -> PathBuf {
+    let stem = golden.file_stem().unwrap_or_default().to_string_lossy();
+    golden.with_file_name(format!("{stem}.{suffix}"))
+}
+
+// On a mismatch saves <golden>.actual.pbm and <golden>.diff.png beside the
+// golden and describes the difference
+pub fn compare<P: AsRef<Path>>(screen: &Framebuffer, golden: P) -> Result<(), String> {
+    let update = std::env::var_os(UPDATE_VARIABLE).is_some_and(|value| value != "0");
+    compare_or_update(screen, golden.as_ref(), update)
+}
+
+fn compare_or_update(screen: &Framebuffer, path: &Path, update: bool) -> Result<(), String> {
+    let golden = match load(path) {
+        Ok(golden) => golden,
+        Err(GoldenError::Io(error)) if error.kind() == std::io::ErrorKind::NotFound => {
+            if update {
+                return save(screen, path).map_err(|error| error.to_string());
+            }
+            return Err(format!(
+                "{} does not exist, run with {UPDATE_VARIABLE}=1 to create it",
+                path.display()
+            ));
+        }
+        Err(error) => return Err(format!("could not load {}: {error}", path.display())),
+    };
+
+    let mismatched = mismatches(screen, &golden);
+    if mismatched.is_empty() {
+        return Ok(());
+    }
+    if update {
+        return save(screen, path).map_err(|error| error.to_string());
+    }
+
+    let actual = sibling(path, "actual.pbm");
+    let diff = sibling(path, "diff.png");
+    let saved = save(screen, &actual)
+        .and_then(|()| Ok(capture::save_png(&diff_image(screen, &golden), &diff)?));
+    let mut message = format!(
+        "screen (hash {:016x}) differs from {} (hash {:016x}) in {} pixels, first at ({}, {})",
+        screen.hash(),
+        path.display(),
+        golden.hash(),
+        mismatched.len(),
+        mismatched[0].column,
+        mismatched[0].row
+    );
+    match saved {
+        Ok(()) => message += &format!(", see {} and {}", actual.display(), diff.display()),
+        Err(error) => message += &format!(", could not save the diff: {error}"),
+    }
+    Err(message)
+}
+
+// Platforms that draw straight into memory never suspend, so this returns
+// without waiting
+pub fn screen<PLATFORM: Platform>(platform: &PLATFORM) -> Framebuffer {
+    async_io::block_on(Framebuffer::capture(platform))
+}
+
+// Goldens are relative to the directory of the calling file. file!() is
+// relative to the package, so the path does not depend on the working directory
+#[macro_export]
+macro_rules! assert_screen_matches {
+    ($emulator:expr, $golden:expr $(,)?) => {{
+        let screen = $crate::golden::screen($emulator.platform());
+        let golden = ::std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join(file!())
+            .parent()
+            .unwrap_or(::std::path::Path::new(""))
+            .join($golden);
+        if let Err(message) = $crate::golden::compare(&screen, &golden) {
+            panic!("{message}");
+        }
+    }};
+}
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod test {
+    use crate::emulator::framebuffer::Framebuffer;
+    use crate::emulator::platform::{Pixel, PixelState};
+    use crate::golden::*;
+
+    fn framebuffer_with_pixels(pixels: &[(u8, u8)]) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new();
+        for &(column, row) in pixels {
+            framebuffer.set_pixel(Pixel { column, row }, PixelState::On);
+        }
+        framebuffer
+    }
+
+    #[test]
+    fn pbm_round_trips() {
+        let framebuffer = framebuffer_with_pixels(&[(0, 0), (63, 0), (5, 17), (63, 31)]);
+        let pbm = to_pbm(&framebuffer);
+        assert!(pbm.starts_with("P1\n64 32\n1 0 0"));
+        assert_eq!(from_pbm(pbm.as_bytes()).unwrap(), framebuffer);
+    }
+
+    #[test]
+    fn raw_pbm_and_comments_load() {
+        let mut pbm = b"P4\n# a comment\n64 32\n".to_vec();
+        let mut raster = vec![0; 8 * 32];
+        raster[0] = 0x80;
+        raster[8 * 2 + 7] = 0x01;
+        pbm.extend(raster);
+
+        assert_eq!(
+            from_pbm(&pbm).unwrap(),
+            framebuffer_with_pixels(&[(0, 0), (63, 2)])
+        );
+    }
+
+    #[test]
+    fn invalid_pbms_are_rejected() {
+        for pbm in [
+            "P2\n64 32\n",
+            "P1\n32 32\n",
+            "P1\n64 32\n1 0 2",
+            "P1\n64 32\n1 0",
+        ] {
+            assert!(from_pbm(pbm.as_bytes()).is_err(), "{pbm}");
+        }
+    }
+
+    #[test]
+    fn scaled_screenshots_load() {
+        let framebuffer = framebuffer_with_pixels(&[(1, 0), (10, 20)]);
+        let mut png_data = vec![];
+        let image = Renderer::new(ColorPalette::default())
+            .with_scale(3)
+            .render(&framebuffer);
+        crate::capture::write_png(&image, &mut png_data).unwrap();
+        assert_eq!(from_png(&png_data).unwrap(), framebuffer);
+    }
+
+    #[test]
+    fn diff_marks_mismatched_pixels() {
+        let screen = framebuffer_with_pixels(&[(0, 0), (1, 0)]);
+        let golden = framebuffer_with_pixels(&[(1, 0), (2, 0)]);
+
+        assert_eq!(
+            mismatches(&screen, &golden),
+            [Pixel { column: 0, row: 0 }, Pixel { column: 2, row: 0 }]
+        );
+        let diff = diff_image(&screen, &golden);
+        assert_eq!(diff.pixel(0, 0), [0xff, 0x30, 0x30, 0xff]);
+        assert_eq!(diff.pixel(1, 0), [0x60, 0x60, 0x60, 0xff]);
+        assert_eq!(diff.pixel(2, 0), [0x30, 0x90, 0xff, 0xff]);
+        assert_eq!(diff.pixel(3, 0), [0x00, 0x00, 0x00, 0xff]);
+    }
+
+    #[test]
+    fn mismatches_save_the_screen_and_a_diff() {
+        let directory = std::env::temp_dir().join(format!("chip8-golden-{}", std::process::id()));
+        let golden = directory.join("screen.pbm");
+        save(&framebuffer_with_pixels(&[(4, 4)]), &golden).unwrap();
+
+        // Not compare(), which would update the golden under UPDATE_GOLDEN=1
+        let screen = framebuffer_with_pixels(&[(4, 4)]);
+        assert_eq!(compare_or_update(&screen, &golden, false), Ok(()));
+
+        let screen = framebuffer_with_pixels(&[(5, 4)]);
+        let message = compare_or_update(&screen, &golden, false).unwrap_err();
+        assert!(
+            message.contains("in 2 pixels, first at (4, 4)"),
+            "{message}"
+        );
+        assert_eq!(load(directory.join("screen.actual.pbm")).unwrap(), screen);
+        assert!(directory.join("screen.diff.png").exists());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
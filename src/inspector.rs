@@ -0,0 +1,238 @@
+// Debug panel for the terminal, redrawn each frame with ANSI escapes next to
+// the display drawn in half-block characters
+mod tests;
+
+use std::fmt::{self, Write};
+
+use crate::emulator::bus::Bus;
+use crate::emulator::cpu_state::CpuState;
+use crate::emulator::observer::Observer;
+use crate::emulator::platform::{
+    KeyState, KeypadNumber, Pixel, PixelState, Platform, KEYPAD_COUNT,
+};
+use crate::emulator::Emulator;
+use crate::keymap::KEYPAD_LAYOUT;
+
+pub const CLEAR_SCREEN: &str = "\x1b[H\x1b[2J";
+
+const RESET: &str = "\x1b[0m";
+const REVERSE: &str = "\x1b[7m";
+const UNDERLINE: &str = "\x1b[4m";
+const CHANGED: &str = "\x1b[1;33m";
+
+const MEMORY_ROW_SIZE: u16 = 16;
+const MEMORY_ROWS: u16 = 4;
+// Instructions shown either side of PC
+const DISASSEMBLY_CONTEXT: u16 = 4;
+
+pub struct Inspector {
+    visible: bool,
+    previous: Option<CpuState>,
+}
+
+impl Default for Inspector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inspector {
+    pub fn new() -> Self {
+        Inspector {
+            visible: true,
+            previous: None,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    // Registers and timers that changed since the last render are highlighted
+    pub async fn render<PLATFORM: Platform, BUS: Bus, OBSERVER: Observer>(
+        &mut self,
+        emulator: &Emulator<PLATFORM, BUS, OBSERVER>,
+    ) -> String {
+        let mut keypad = [KeyState::Off; KEYPAD_COUNT as usize];
+        for (key, state) in keypad.iter_mut().enumerate() {
+            *state = emulator
+                .platform()
+                .read_keypress_state(KeypadNumber(key as u8))
+                .await;
+        }
+
+        let state = emulator.cpu_state();
+        let previous = self.previous.replace(state).unwrap_or(state);
+
+        let display = display_rows(emulator.platform()).await;
+        let mut panel = String::new();
+        write_panel(&mut panel, emulator, state, previous, keypad)
+            .expect("writing to a String cannot fail");
+        side_by_side(&display, &panel)
+    }
+}
+
+// Two pixel rows per line, framed so that the edges of a blank display show
+async fn display_rows<PLATFORM: Platform>(platform: &PLATFORM) -> Vec<String> {
+    let width = platform.get_display_width().await;
+    let height = platform.get_display_height().await;
+    let is_on = |column, row| async move {
+        row < height && platform.get_pixel(Pixel { column, row }).await == PixelState::On
+    };
+
+    let border = "─".repeat(width as usize);
+    let mut rows = vec![format!("┌{border}┐")];
+    for row in (0..height).step_by(2) {
+        let mut line = String::from("│");
+        for column in 0..width {
+            line.push(
+                match (is_on(column, row).await, is_on(column, row + 1).await) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                },
+            );
+        }
+        line.push('│');
+        rows.push(line);
+    }
+    rows.push(format!("└{border}┘"));
+    rows
+}
+
+fn side_by_side(display: &[String], panel: &str) -> String {
+    let blank = " ".repeat(display.first().map_or(0, |row| row.chars().count()));
+    let panel_lines: Vec<&str> = panel.lines().collect();
+    let mut combined = String::new();
+    for line in 0..display.len().max(panel_lines.len()) {
+        let left = display.get(line).unwrap_or(&blank);
+        let right = panel_lines.get(line).copied().unwrap_or_default();
+        combined.push_str(left);
+        combined.push_str("  ");
+        combined.push_str(right);
+        combined.push('\n');
+    }
+    combined
+}
+
+fn write_panel<PLATFORM: Platform, BUS: Bus, OBSERVER: Observer>(
+    panel: &mut String,
+    emulator: &Emulator<PLATFORM, BUS, OBSERVER>,
+    state: CpuState,
+    previous: CpuState,
+    keypad: [KeyState; KEYPAD_COUNT as usize],
+) -> fmt::Result {
+    let bus = emulator.bus();
+
+    write!(panel, "PC {:#05X}  ", state.program_counter)?;
+    let i_register = format!("I {:#05X}", state.i_register);
+    styled(panel, state.i_register != previous.i_register, &i_register)?;
+    write!(panel, "  SP {}  ", state.stack_pointer)?;
+    let delay_timer = format!("DT {:02X}", state.delay_timer);
+    styled(
+        panel,
+        state.delay_timer != previous.delay_timer,
+        &delay_timer,
+    )?;
+    write!(panel, "  ")?;
+    let sound_timer = format!("ST {:02X}", state.sound_timer);
+    styled(
+        panel,
+        state.sound_timer != previous.sound_timer,
+        &sound_timer,
+    )?;
+    writeln!(panel)?;
+
+    writeln!(panel)?;
+    for (row, values) in state.v_registers.chunks(8).enumerate() {
+        for (column, value) in values.iter().enumerate() {
+            let register = row * 8 + column;
+            let changed = *value != previous.v_registers[register];
+            styled(panel, changed, &format!("V{register:X} {value:02X}"))?;
+            write!(panel, "  ")?;
+        }
+        writeln!(panel)?;
+    }
+
+    write!(panel, "\nStack")?;
+    if emulator.call_stack().is_empty() {
+        write!(panel, " empty")?;
+    }
+    for address in emulator.call_stack().backtrace() {
+        write!(panel, " {address:#05X}")?;
+    }
+    writeln!(panel)?;
+
+    writeln!(panel)?;
+    let pc = state.program_counter;
+    let first = pc.saturating_sub(DISASSEMBLY_CONTEXT * 2);
+    for address in (first..=pc.saturating_add(DISASSEMBLY_CONTEXT * 2)).step_by(2) {
+        let (Some(high), Some(low)) = (bus.peek(address), bus.peek(address.wrapping_add(1))) else {
+            break;
+        };
+        let opcode = u16::from_be_bytes([high, low]);
+        let marker = if address == pc { ">" } else { " " };
+        write!(panel, "{marker} {address:#05X}  {opcode:04X}  ")?;
+        match emulator.decoder().decode(&[high, low]) {
+            Some(instruction) => writeln!(panel, "{instruction}")?,
+            None => writeln!(panel, "???")?,
+        }
+    }
+
+    writeln!(panel)?;
+    let size = bus.size().min(u16::MAX as usize + 1);
+    let last_row = (size as u16).wrapping_sub(1) & !(MEMORY_ROW_SIZE - 1);
+    let pc_rows = (pc & !(MEMORY_ROW_SIZE - 1)).saturating_sub(MEMORY_ROW_SIZE);
+    let i_rows = state.i_register & !(MEMORY_ROW_SIZE - 1);
+    for start in [pc_rows, i_rows] {
+        for row in 0..MEMORY_ROWS {
+            let row_address = start.saturating_add(row * MEMORY_ROW_SIZE).min(last_row);
+            write!(panel, "{row_address:#05X} ")?;
+            for address in row_address..row_address.saturating_add(MEMORY_ROW_SIZE) {
+                let Some(value) = bus.peek(address) else {
+                    break;
+                };
+                write!(panel, " ")?;
+                let style = if address == pc || address == pc.wrapping_add(1) {
+                    Some(REVERSE)
+                } else if address == state.i_register {
+                    Some(UNDERLINE)
+                } else {
+                    None
+                };
+                match style {
+                    Some(style) => write!(panel, "{style}{value:02X}{RESET}")?,
+                    None => write!(panel, "{value:02X}")?,
+                }
+            }
+            writeln!(panel)?;
+        }
+        writeln!(panel)?;
+    }
+
+    for row in KEYPAD_LAYOUT {
+        for key in row {
+            if keypad[key as usize] == KeyState::On {
+                write!(panel, " {REVERSE}{key:X}{RESET}")?;
+            } else {
+                write!(panel, " {key:X}")?;
+            }
+        }
+        writeln!(panel)?;
+    }
+
+    Ok(())
+}
+
+fn styled(panel: &mut String, changed: bool, text: &str) -> fmt::Result {
+    if changed {
+        write!(panel, "{CHANGED}{text}{RESET}")
+    } else {
+        write!(panel, "{text}")
+    }
+}
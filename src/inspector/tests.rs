@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod test {
+    use crate::emulator::instruction::{Decoder, Instruction};
+    use crate::emulator::platform::headless::HeadlessPlatform;
+    use crate::inspector::*;
+
+    // Decodes every opcode as CLS
+    struct ClearDecoder;
+
+    impl Decoder for ClearDecoder {
+        fn decode(&self, _opcode: &[u8; 2]) -> Option<Instruction> {
+            Some(Instruction::ClearDisplay)
+        }
+    }
+
+    fn emulator() -> Emulator<HeadlessPlatform> {
+        Emulator::new(HeadlessPlatform::new(1))
+    }
+
+    #[tokio::test]
+    async fn disassembles_around_program_counter() {
+        // V0 = 1, V0 += V1, draw, call 0x200, invalid
+        let rom = [0x60, 0x01, 0x80, 0x14, 0xD0, 0x15, 0x22, 0x00, 0xFF, 0xFF];
+        let mut emulator = emulator();
+        emulator.load_into_memory(&rom).await.unwrap();
+        emulator.run_instruction_loop().await.unwrap();
+
+        let panel = Inspector::new().render(&emulator).await;
+
+        assert!(panel.contains("  0x200  6001  LD V0, 0x01\n"));
+        assert!(panel.contains("> 0x202  8014  ADD V0, V1\n"));
+        assert!(panel.contains("  0x204  D015  DRW V0, V1, 5\n"));
+        assert!(panel.contains("  0x206  2200  CALL 0x200\n"));
+        assert!(panel.contains("  0x208  FFFF  ???\n"));
+    }
+
+    #[tokio::test]
+    async fn disassembles_with_the_emulators_decoder() {
+        let mut emulator = emulator();
+        emulator.load_into_memory(&[0x60, 0x01]).await.unwrap();
+        emulator.set_decoder(&ClearDecoder);
+
+        let panel = Inspector::new().render(&emulator).await;
+
+        assert!(panel.contains("> 0x200  6001  CLS\n"));
+    }
+
+    #[tokio::test]
+    async fn draws_the_display_next_to_the_panel() {
+        // I = font sprite for V0, draw it at V0, V0
+        let mut emulator = emulator();
+        emulator
+            .load_into_memory(&[0xF0, 0x29, 0xD0, 0x05])
+            .await
+            .unwrap();
+        emulator.run_instruction_loop().await.unwrap();
+        emulator.run_instruction_loop().await.unwrap();
+
+        let panel = Inspector::new().render(&emulator).await;
+        let lines: Vec<&str> = panel.lines().collect();
+
+        let blank = " ".repeat(60);
+        assert_eq!(
+            lines[0],
+            format!(
+                "┌{}┐  PC 0x204  I 0x050  SP 0  DT 00  ST 00",
+                "─".repeat(64)
+            )
+        );
+        assert!(lines[1].starts_with(&format!("│█▀▀█{blank}│  ")));
+        assert!(lines[2].starts_with(&format!("│█  █{blank}│  ")));
+        assert!(lines[3].starts_with(&format!("│▀▀▀▀{blank}│  ")));
+        assert!(lines[17].starts_with(&format!("└{}┘  ", "─".repeat(64))));
+        // The panel is longer than the display
+        assert!(lines[18].starts_with(&" ".repeat(68)));
+    }
+
+    #[tokio::test]
+    async fn highlights_changes_since_last_render() {
+        // V5 = 0x42, then loop
+        let mut emulator = emulator();
+        emulator
+            .load_into_memory(&[0x65, 0x42, 0x12, 0x02])
+            .await
+            .unwrap();
+        let mut inspector = Inspector::new();
+        inspector.render(&emulator).await;
+
+        emulator.run_instruction_loop().await.unwrap();
+        let panel = inspector.render(&emulator).await;
+        assert!(panel.contains(&format!("{CHANGED}V5 42{RESET}")));
+        assert!(!panel.contains(&format!("{CHANGED}V4")));
+
+        emulator.run_instruction_loop().await.unwrap();
+        let panel = inspector.render(&emulator).await;
+        assert!(!panel.contains(CHANGED));
+    }
+
+    #[tokio::test]
+    async fn highlights_memory_stack_and_keys() {
+        // I = 0x300, call 0x206
+        let mut emulator = emulator();
+        emulator
+            .load_into_memory(&[0xA3, 0x00, 0x22, 0x06])
+            .await
+            .unwrap();
+        emulator.run_instruction_loop().await.unwrap();
+        emulator.run_instruction_loop().await.unwrap();
+        emulator
+            .platform_mut()
+            .set_key(KeypadNumber(0xE), KeyState::On);
+
+        let panel = Inspector::new().render(&emulator).await;
+
+        assert!(panel.contains("Stack 0x204\n"));
+        assert!(panel.contains(&format!(
+            "0x200  A3 00 22 06 00 00 {REVERSE}00{RESET} {REVERSE}00{RESET}"
+        )));
+        assert!(panel.contains(&format!("0x300  {UNDERLINE}00{RESET} 00")));
+        assert!(panel.contains(&format!(" 7 8 9 {REVERSE}E{RESET}\n")));
+    }
+
+    #[test]
+    fn toggles_visibility() {
+        let mut inspector = Inspector::new();
+        assert!(inspector.is_visible());
+        inspector.toggle();
+        assert!(!inspector.is_visible());
+    }
+}
@@ -0,0 +1,142 @@
+// Host keyboard characters to CHIP-8 keypad keys. Presets put the keypad on
+// the 4x4 block of keys under 1-4 for each keyboard layout.
+mod tests;
+
+use alloc::collections::{BTreeMap, BTreeSet};
+
+use crate::emulator::platform::{KeyState, KeypadNumber};
+
+// COSMAC VIP keypad, row by row
+pub const KEYPAD_LAYOUT: [[u8; 4]; 4] = [
+    [0x1, 0x2, 0x3, 0xC],
+    [0x4, 0x5, 0x6, 0xD],
+    [0x7, 0x8, 0x9, 0xE],
+    [0xA, 0x0, 0xB, 0xF],
+];
+
+pub const PRESETS: [&str; 3] = ["qwerty", "azerty", "dvorak"];
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Keymap {
+    bindings: BTreeMap<char, KeypadNumber>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::qwerty()
+    }
+}
+
+impl Keymap {
+    pub fn empty() -> Self {
+        Keymap {
+            bindings: BTreeMap::new(),
+        }
+    }
+
+    pub fn qwerty() -> Self {
+        Self::from_rows(["1234", "qwer", "asdf", "zxcv"])
+    }
+
+    pub fn azerty() -> Self {
+        Self::from_rows(["1234", "azer", "qsdf", "wxcv"])
+    }
+
+    pub fn dvorak() -> Self {
+        Self::from_rows(["1234", "',.p", "aoeu", ";qjk"])
+    }
+
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "qwerty" => Some(Self::qwerty()),
+            "azerty" => Some(Self::azerty()),
+            "dvorak" => Some(Self::dvorak()),
+            _ => None,
+        }
+    }
+
+    fn from_rows(rows: [&str; 4]) -> Self {
+        let mut keymap = Self::empty();
+        for (row, keys) in KEYPAD_LAYOUT.iter().zip(rows) {
+            for (number, key) in row.iter().zip(keys.chars()) {
+                keymap.bind(key, KeypadNumber(*number));
+            }
+        }
+        keymap
+    }
+
+    pub fn with_binding(mut self, key: char, number: KeypadNumber) -> Self {
+        self.bind(key, number);
+        self
+    }
+
+    // A host key drives one keypad key, several host keys can share one
+    pub fn bind(&mut self, key: char, number: KeypadNumber) {
+        self.bindings.insert(key.to_ascii_lowercase(), number);
+    }
+
+    pub fn unbind(&mut self, key: char) {
+        self.bindings.remove(&key.to_ascii_lowercase());
+    }
+
+    // Letters match either case
+    pub fn keypad_number(&self, key: char) -> Option<KeypadNumber> {
+        self.bindings.get(&key.to_ascii_lowercase()).copied()
+    }
+
+    pub fn bindings(&self) -> impl Iterator<Item = (char, KeypadNumber)> + '_ {
+        self.bindings.iter().map(|(key, number)| (*key, *number))
+    }
+}
+
+// Host key state for frontends, read_keypress_state can answer from `state`
+#[derive(Debug, Clone, Default)]
+pub struct MappedKeypad {
+    keymap: Keymap,
+    held: BTreeSet<char>,
+}
+
+impl MappedKeypad {
+    pub fn new(keymap: Keymap) -> Self {
+        MappedKeypad {
+            keymap,
+            held: BTreeSet::new(),
+        }
+    }
+
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
+    // Both return the keypad key the host key is bound to, if any
+    pub fn key_down(&mut self, key: char) -> Option<KeypadNumber> {
+        self.held.insert(key.to_ascii_lowercase());
+        self.keymap.keypad_number(key)
+    }
+
+    pub fn key_up(&mut self, key: char) -> Option<KeypadNumber> {
+        self.held.remove(&key.to_ascii_lowercase());
+        self.keymap.keypad_number(key)
+    }
+
+    pub fn release_all(&mut self) {
+        self.held.clear();
+    }
+
+    // On while any host key bound to it is held
+    pub fn state(&self, number: KeypadNumber) -> KeyState {
+        let held = self
+            .held
+            .iter()
+            .any(|key| self.keymap.keypad_number(*key) == Some(number));
+        if held {
+            KeyState::On
+        } else {
+            KeyState::Off
+        }
+    }
+}
@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod test {
+    use crate::keymap::*;
+
+    #[test]
+    fn presets_cover_the_keypad_in_the_same_positions() {
+        for name in PRESETS {
+            let keymap = Keymap::preset(name).unwrap();
+            let numbers: BTreeSet<u8> = keymap.bindings().map(|(_, number)| number.0).collect();
+            assert_eq!(numbers.len(), 16, "{name}");
+        }
+
+        assert_eq!(Keymap::qwerty().keypad_number('1'), Some(KeypadNumber(0x1)));
+        assert_eq!(Keymap::qwerty().keypad_number('V'), Some(KeypadNumber(0xF)));
+        assert_eq!(Keymap::azerty().keypad_number('a'), Some(KeypadNumber(0x4)));
+        assert_eq!(Keymap::azerty().keypad_number('w'), Some(KeypadNumber(0xA)));
+        assert_eq!(Keymap::dvorak().keypad_number('o'), Some(KeypadNumber(0x8)));
+        assert_eq!(Keymap::dvorak().keypad_number('x'), None);
+        assert_eq!(Keymap::preset("colemak"), None);
+    }
+
+    #[test]
+    fn bindings_override_presets() {
+        let keymap = Keymap::qwerty()
+            .with_binding('Q', KeypadNumber(0x0))
+            .with_binding(' ', KeypadNumber(0x5));
+
+        assert_eq!(keymap.keypad_number('q'), Some(KeypadNumber(0x0)));
+        assert_eq!(keymap.keypad_number(' '), Some(KeypadNumber(0x5)));
+        assert_eq!(keymap.keypad_number('w'), Some(KeypadNumber(0x5)));
+    }
+
+    #[test]
+    fn keypad_key_stays_on_while_any_bound_key_is_held() {
+        let keymap = Keymap::qwerty().with_binding(' ', KeypadNumber(0x5));
+        let mut keypad = MappedKeypad::new(keymap);
+
+        assert_eq!(keypad.key_down('w'), Some(KeypadNumber(0x5)));
+        keypad.key_down(' ');
+        keypad.key_up('W');
+        assert_eq!(keypad.state(KeypadNumber(0x5)), KeyState::On);
+
+        keypad.key_up(' ');
+        assert_eq!(keypad.state(KeypadNumber(0x5)), KeyState::Off);
+        assert_eq!(keypad.key_down('+'), None);
+    }
+}
@@ -0,0 +1,28 @@
+#![cfg_attr(not(any(test, feature = "std", feature = "wasm")), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod audio;
+#[cfg(feature = "batch")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod capture;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod emulator;
+#[cfg(feature = "std")]
+pub mod gamepad;
+#[cfg(feature = "std")]
+pub mod golden;
+#[cfg(feature = "std")]
+pub mod inspector;
+#[cfg(feature = "alloc")]
+pub mod keymap;
+#[cfg(feature = "alloc")]
+pub mod render;
+#[cfg(feature = "rom-database")]
+pub mod rom_database;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
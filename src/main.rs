@@ -1,8 +1,584 @@
-#![allow(
-    dead_code,
-    )]
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::Instant;
 
-mod emulator;
-fn main() {
-    println!("Hello, world!");
+use clap::{Parser, Subcommand};
+
+use chip8_rs::audio::{AudioRecorder, SquareWave};
+use chip8_rs::batch::{self, Manifest, Report};
+use chip8_rs::capture::{self, GifRecorder};
+use chip8_rs::config::Config;
+use chip8_rs::emulator::call_stack::{StackDepth, MAX_STACK_DEPTH};
+use chip8_rs::emulator::framebuffer::DISPLAY_WIDTH;
+use chip8_rs::emulator::platform::headless::HeadlessPlatform;
+use chip8_rs::emulator::platform::{KeyState, KeypadNumber, FRAME_DURATION, KEYPAD_COUNT};
+use chip8_rs::emulator::quirks::Quirks;
+use chip8_rs::emulator::{Emulator, MAX_ROM_SIZE};
+use chip8_rs::gamepad::{self, EventReader, Gamepad, InputEvent, Profile};
+use chip8_rs::inspector::{Inspector, CLEAR_SCREEN};
+use chip8_rs::keymap::{Keymap, MappedKeypad};
+use chip8_rs::render::{ColorPalette, FrameBlender, Renderer};
+use chip8_rs::rom_database::{self, RomDatabase, RomPreset};
+
+#[derive(Parser)]
+#[command(version, about = "CHIP-8 emulator", subcommand_negates_reqs = true)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// ROM to load at 0x200
+    #[arg(required = true)]
+    rom: Option<PathBuf>,
+
+    /// Number of 60 Hz frames to run, 600 by default. With --inspect the default is to run until
+    /// stdin is closed
+    #[arg(long)]
+    frames: Option<u32>,
+
+    /// Seed for the random number generator
+    #[arg(long, default_value_t = 1)]
+    seed: u32,
+
+    /// Save a PNG of the display once this many instructions have executed
+    #[arg(long, value_name = "CYCLE")]
+    screenshot_at_cycle: Option<u64>,
+
+    /// Where to save the screenshot
+    #[arg(long, value_name = "PATH", default_value = "screenshot.png")]
+    screenshot: PathBuf,
+
+    /// Record the display into an animated GIF
+    #[arg(long, value_name = "PATH")]
+    record_gif: Option<PathBuf>,
+
+    /// First frame to record into the GIF
+    #[arg(long, value_name = "FRAME", default_value_t = 0)]
+    record_start: u32,
+
+    /// Record the buzzer into a WAV file
+    #[arg(long, value_name = "PATH")]
+    wav: Option<PathBuf>,
+
+    /// Pixel scale factor for screenshots and recordings, up to 1023
+    #[arg(long, default_value_t = 8, value_parser = clap::value_parser!(u16).range(1..=MAX_SCALE as i64))]
+    scale: u16,
+
+    /// Load the ROM database from a chip-8-database checkout instead of the bundled copy
+    #[arg(long, value_name = "DIR")]
+    rom_database: Option<PathBuf>,
+
+    /// Do not look the ROM up in the ROM database
+    #[arg(long, conflicts_with = "rom_database")]
+    no_rom_database: bool,
+
+    /// Use the quirks and speed of a platform from the ROM database, e.g. originalChip8
+    #[arg(long, value_name = "ID")]
+    platform: Option<String>,
+
+    /// Instructions executed per 60 Hz frame
+    #[arg(long, value_name = "COUNT")]
+    tickrate: Option<u32>,
+
+    /// Turn a quirk on or off, e.g. --quirk shift=true, using chip-8-database names
+    #[arg(long, value_name = "NAME=BOOL", value_parser = parse_quirk)]
+    quirk: Vec<(String, bool)>,
+
+    /// Maximum nested subroutine calls, 12 on the COSMAC VIP, or "unbounded"
+    #[arg(long, value_name = "DEPTH", default_value = "16", value_parser = parse_stack_depth)]
+    stack_depth: StackDepth,
+
+    /// Print executed code the ROM overwrote once the run ends
+    #[arg(long)]
+    report_code_modifications: bool,
+
+    /// Display colors as "#rrggbb,#rrggbb" for off and on pixels, or 4 or 16 colors for XO-CHIP
+    /// planes
+    #[arg(long, value_name = "OFF,ON", value_parser = parse_colors)]
+    colors: Option<ColorPalette>,
+
+    /// Fraction of a pixel's brightness kept each frame after it turns off, from 0 to 1, to
+    /// hide flicker in screenshots
+    #[arg(long, value_name = "PERSISTENCE", default_value_t = 0.0, value_parser = parse_persistence)]
+    phosphor: f32,
+
+    /// Show each frame together with the one before it in screenshots and recordings, which
+    /// hides sprites flickering as they are erased and redrawn
+    #[arg(long)]
+    blend_frames: bool,
+
+    /// Run in real time with a debug panel of memory, registers, stack and keypad. An empty line
+    /// on stdin hides or shows it, other lines press the keys they contain, and Ctrl-D quits
+    #[arg(long)]
+    inspect: bool,
+
+    /// TOML file with keymap, colors, tickrate and quirks, see src/config.rs
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Host keys for the keypad, qwerty, azerty or dvorak
+    #[arg(long, value_name = "PRESET", value_parser = parse_keymap)]
+    keymap: Option<Keymap>,
+
+    /// Run in real time reading a controller from an evdev device such as /dev/input/event3, a
+    /// recording of one, or "auto" for the first controller found
+    #[arg(long, value_name = "DEVICE")]
+    gamepad: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run every ROM in a directory headless and check the final screens against a manifest,
+    /// exiting with an error if any ROM fails
+    Batch {
+        /// Directory of ROMs
+        dir: PathBuf,
+
+        /// Expected hashes, cycle counts, inputs and quirks, see src/batch.rs. Defaults to
+        /// manifest.toml in the directory if there is one
+        #[arg(long, value_name = "PATH")]
+        manifest: Option<PathBuf>,
+
+        /// Number of ROMs to run at once, by default one per CPU
+        #[arg(long, value_name = "COUNT")]
+        jobs: Option<usize>,
+
+        /// Write a JUnit XML report
+        #[arg(long, value_name = "PATH")]
+        junit: Option<PathBuf>,
+
+        /// Write a JSON report
+        #[arg(long, value_name = "PATH")]
+        json: Option<PathBuf>,
+    },
+}
+
+// How long a key typed on stdin stays pressed
+const KEY_HOLD_FRAMES: u32 = 6;
+const DEFAULT_FRAMES: u32 = 600;
+// Image sizes are 16 bits in GIFs
+const MAX_SCALE: u16 = u16::MAX / DISPLAY_WIDTH as u16;
+
+fn parse_quirk(arg: &str) -> Result<(String, bool), String> {
+    let (name, value) = arg.split_once('=').ok_or("expected NAME=BOOL")?;
+    let value = value
+        .parse()
+        .map_err(|_| format!("{value} is not true or false"))?;
+    set_quirk(&mut Quirks::default(), name, value)?;
+    Ok((name.to_string(), value))
+}
+
+fn set_quirk(quirks: &mut Quirks, name: &str, value: bool) -> Result<(), String> {
+    let quirk = quirks
+        .by_name_mut(name)
+        .ok_or(format!("unknown quirk {name}"))?;
+    *quirk = value;
+    Ok(())
+}
+
+fn parse_keymap(arg: &str) -> Result<Keymap, String> {
+    Keymap::preset(arg).ok_or(format!("unknown keymap {arg}"))
+}
+
+fn parse_stack_depth(arg: &str) -> Result<StackDepth, String> {
+    match arg {
+        "unbounded" => Ok(StackDepth::Unbounded),
+        depth => match depth.parse() {
+            Ok(depth) if depth <= MAX_STACK_DEPTH => Ok(StackDepth::Limited(depth)),
+            Ok(_) => Err(format!("at most {MAX_STACK_DEPTH}, or unbounded")),
+            Err(_) => Err(format!("{depth} is not a number or unbounded")),
+        },
+    }
+}
+
+fn parse_colors(arg: &str) -> Result<ColorPalette, String> {
+    let colors = arg
+        .split(',')
+        .map(|color| rom_database::parse_color(color).ok_or(format!("invalid color {color}")))
+        .collect::<Result<Vec<_>, _>>()?;
+    ColorPalette::new(&colors).ok_or("expected 2, 4 or 16 colors".into())
+}
+
+fn parse_persistence(arg: &str) -> Result<f32, String> {
+    arg.parse()
+        .ok()
+        .filter(|persistence| (0.0..=1.0).contains(persistence))
+        .ok_or(format!("{arg} is not between 0 and 1"))
+}
+
+fn read_stdin_lines() -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+fn read_gamepad_events(device: &Path) -> Result<Receiver<InputEvent>, Box<dyn Error>> {
+    let device = if device == Path::new("auto") {
+        #[cfg(target_os = "linux")]
+        let found = gamepad::find_gamepads()?;
+        #[cfg(not(target_os = "linux"))]
+        let found: Vec<PathBuf> = Vec::new();
+        found.into_iter().next().ok_or("no gamepad found")?
+    } else {
+        device.to_path_buf()
+    };
+    let file = File::open(&device)
+        .map_err(|error| format!("could not open {}: {error}", device.display()))?;
+
+    // Recordings play back at the pace they were recorded, device events are
+    // never early so they pass straight through
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let start = Instant::now();
+        let mut first_event = None;
+        for event in EventReader::new(file) {
+            let Ok(event) = event else { break };
+            let first_event = *first_event.get_or_insert(event.time);
+            let due = start + event.time.saturating_sub(first_event);
+            std::thread::sleep(due.saturating_duration_since(Instant::now()));
+            if sender.send(event).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(receiver)
+}
+
+struct Settings {
+    quirks: Quirks,
+    tickrate: Option<u32>,
+    palette: ColorPalette,
+    keymap: Keymap,
+    gamepad: Profile,
+}
+
+// Settings from the ROM database, overridden by the config file and then by
+// command line flags
+fn resolve_settings(args: &Args, rom: &[u8]) -> Result<Settings, Box<dyn Error>> {
+    let database = match &args.rom_database {
+        Some(path) => Some(RomDatabase::load_dir(path)?),
+        None if args.no_rom_database => None,
+        None => Some(RomDatabase::bundled()),
+    };
+
+    let preset: Option<RomPreset> = match (&database, &args.platform) {
+        (Some(database), Some(platform)) => Some(
+            database
+                .platform_preset(platform)
+                .ok_or(format!("unknown platform {platform}"))?,
+        ),
+        (Some(database), None) => database.lookup(rom),
+        (None, Some(_)) => return Err("--platform needs the ROM database".into()),
+        (None, None) => None,
+    };
+
+    let config = match &args.config {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    let config = config.for_rom(rom);
+
+    let mut quirks = preset
+        .as_ref()
+        .map(|preset| preset.quirks)
+        .unwrap_or_default();
+    config.apply_quirks(&mut quirks);
+    for (name, value) in &args.quirk {
+        set_quirk(&mut quirks, name, *value)?;
+    }
+
+    let tickrate = args
+        .tickrate
+        .or(config.tickrate)
+        .or(preset.as_ref().map(|preset| preset.instructions_per_frame));
+
+    // Database controls only when the ROM names some
+    let gamepad = config
+        .gamepad
+        .clone()
+        .or(preset
+            .as_ref()
+            .filter(|preset| !preset.keys.is_empty())
+            .map(|preset| Profile::from_named_keys(&preset.keys)))
+        .unwrap_or_default();
+
+    let palette = args
+        .colors
+        .clone()
+        .or(config.palette.clone())
+        .or(preset.and_then(|preset| preset.palette))
+        .unwrap_or_default();
+
+    let keymap = args
+        .keymap
+        .clone()
+        .or(config.keymap.clone())
+        .unwrap_or_default();
+
+    Ok(Settings {
+        quirks,
+        tickrate,
+        palette,
+        keymap,
+        gamepad,
+    })
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    if let Some(Command::Batch {
+        dir,
+        manifest,
+        jobs,
+        junit,
+        json,
+    }) = &args.command
+    {
+        return run_batch(
+            dir,
+            manifest.as_deref(),
+            *jobs,
+            junit.as_deref(),
+            json.as_deref(),
+        );
+    }
+
+    let rom = std::fs::read(args.rom.as_ref().expect("clap requires a ROM"))?;
+    if rom.len() > MAX_ROM_SIZE {
+        return Err(format!(
+            "ROM is {} bytes, at most {MAX_ROM_SIZE} fit in memory",
+            rom.len()
+        )
+        .into());
+    }
+
+    async_io::block_on(run(&args, &rom))
+}
+
+fn run_batch(
+    dir: &Path,
+    manifest: Option<&Path>,
+    jobs: Option<usize>,
+    junit: Option<&Path>,
+    json: Option<&Path>,
+) -> Result<(), Box<dyn Error>> {
+    let default_manifest = dir.join(batch::MANIFEST_FILE_NAME);
+    let manifest = match manifest {
+        Some(path) => Manifest::load(path)?,
+        None if default_manifest.exists() => Manifest::load(default_manifest)?,
+        None => Manifest::default(),
+    };
+    let jobs_to_run = batch::collect_jobs(dir, &manifest)?;
+    let workers = jobs
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |workers| workers.get()));
+    let report = Report {
+        results: batch::run_jobs(&jobs_to_run, workers),
+    };
+
+    for result in &report.results {
+        let status = match &result.outcome {
+            batch::Outcome::Passed => "ok".to_string(),
+            batch::Outcome::Failed(message) => format!("FAILED: {message}"),
+            batch::Outcome::Error(message) => format!("ERROR: {message}"),
+        };
+        println!(
+            "{} ... {status} ({} cycles, {:016x}, {:.3}s)",
+            result.name,
+            result.cycles,
+            result.hash,
+            result.duration.as_secs_f64()
+        );
+    }
+    println!(
+        "{} passed, {} failed, {} errors",
+        report.passed(),
+        report.failed(),
+        report.errors()
+    );
+
+    if let Some(path) = junit {
+        report.write_junit(File::create(path)?)?;
+    }
+    if let Some(path) = json {
+        std::fs::write(path, report.to_json())?;
+    }
+
+    if report.passed() < report.results.len() {
+        return Err("some ROMs did not pass".into());
+    }
+    Ok(())
+}
+
+async fn run(args: &Args, rom: &[u8]) -> Result<(), Box<dyn Error>> {
+    let settings = resolve_settings(args, rom)?;
+    let mut renderer = Renderer::new(settings.palette.clone())
+        .with_scale(args.scale)
+        .with_phosphor_decay(args.phosphor);
+    // Unscaled, the recorder scales when saving
+    let mut gif_renderer =
+        Renderer::new(settings.palette.clone()).with_phosphor_decay(args.phosphor);
+
+    let mut emulator = Emulator::new(HeadlessPlatform::new(args.seed));
+    emulator.set_quirks(settings.quirks);
+    emulator
+        .set_stack_depth(args.stack_depth)
+        .map_err(|fault| fault.to_string())?;
+    if let Some(tickrate) = settings.tickrate {
+        emulator.set_instructions_per_frame(tickrate);
+    }
+    emulator.load_into_memory(rom).await?;
+
+    let mut recorder = GifRecorder::new(args.scale);
+    let mut audio_recorder = AudioRecorder::new(SquareWave::default());
+    let mut blender = FrameBlender::new();
+    let mut cycle: u64 = 0;
+
+    let mut inspector = Inspector::new();
+    let stdin_lines = args.inspect.then(read_stdin_lines);
+    let gamepad_events = args
+        .gamepad
+        .as_deref()
+        .map(read_gamepad_events)
+        .transpose()?;
+    let realtime = args.inspect || gamepad_events.is_some();
+    let mut keypad = MappedKeypad::new(settings.keymap);
+    let mut gamepad = Gamepad::new(settings.gamepad);
+    // Host keys typed on stdin and the frame they are released on
+    let mut typed_keys: BTreeMap<char, u32> = BTreeMap::new();
+
+    // None runs until stdin is closed
+    let frames = match args.frames {
+        None if args.inspect => None,
+        frames => Some(frames.unwrap_or(DEFAULT_FRAMES)),
+    };
+
+    'frames: for frame in 0.. {
+        if frames.is_some_and(|frames| frame >= frames) {
+            break;
+        }
+        if let Some(stdin_lines) = &stdin_lines {
+            loop {
+                let line = match stdin_lines.try_recv() {
+                    Ok(line) => line,
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) if frames.is_none() => break 'frames,
+                    Err(TryRecvError::Disconnected) => break,
+                };
+                if line.is_empty() {
+                    inspector.toggle();
+                    eprint!("{CLEAR_SCREEN}");
+                }
+                for key in line.chars() {
+                    keypad.key_down(key);
+                    typed_keys.insert(key, frame + KEY_HOLD_FRAMES);
+                }
+            }
+            typed_keys.retain(|&key, &mut release| {
+                if release <= frame {
+                    keypad.key_up(key);
+                }
+                release > frame
+            });
+        }
+        if let Some(gamepad_events) = &gamepad_events {
+            for event in gamepad_events.try_iter() {
+                gamepad.handle_event(event);
+            }
+        }
+        if realtime {
+            for number in (0..KEYPAD_COUNT).map(KeypadNumber) {
+                let state = match gamepad.state(number) {
+                    KeyState::On => KeyState::On,
+                    KeyState::Off => keypad.state(number),
+                };
+                emulator.platform_mut().set_key(number, state);
+            }
+        }
+
+        for _ in 0..emulator.instructions_per_frame() {
+            if let Err(fault) = emulator.run_instruction_loop().await {
+                let backtrace: Vec<String> = emulator
+                    .call_stack()
+                    .backtrace()
+                    .map(|address| format!("{address:#05X}"))
+                    .collect();
+                return Err(format!("{fault}, returning to [{}]", backtrace.join(", ")).into());
+            }
+            cycle += 1;
+
+            if args.screenshot_at_cycle == Some(cycle) {
+                let framebuffer = emulator.platform().framebuffer();
+                let image = if args.blend_frames {
+                    renderer.render(&blender.blend(framebuffer))
+                } else {
+                    renderer.render(framebuffer)
+                };
+                capture::save_png(&image, &args.screenshot)?;
+            }
+        }
+        emulator.handle_timers().await;
+        if args.wav.is_some() {
+            audio_recorder.record_frame(emulator.platform().buzzer());
+        }
+
+        let framebuffer = emulator.platform().framebuffer();
+        let shown = if args.blend_frames {
+            blender.next_frame(framebuffer)
+        } else {
+            framebuffer.clone()
+        };
+
+        // Decay needs every frame up to the screenshot
+        if args.phosphor > 0.0 && args.screenshot_at_cycle.is_some_and(|at| at > cycle) {
+            renderer.render(&shown);
+        }
+
+        if args.inspect && inspector.is_visible() {
+            eprint!("{CLEAR_SCREEN}{}", inspector.render(&emulator).await);
+        }
+        if realtime {
+            async_io::Timer::after(FRAME_DURATION).await;
+        }
+
+        if args.record_gif.is_some() {
+            // Rendered before recording starts too, so that decay carries over
+            let image = gif_renderer.render(&shown);
+            if frame >= args.record_start {
+                recorder.record_frame(&image);
+            }
+        }
+    }
+
+    if let Some(path) = &args.record_gif {
+        recorder.save_gif(path)?;
+    }
+    if let Some(path) = &args.wav {
+        audio_recorder.save_wav(path)?;
+    }
+
+    if args.report_code_modifications {
+        for (address, modification) in emulator.code_modifications() {
+            eprintln!(
+                "{address:#05X} overwritten after running, first by {:#05X}, {} writes",
+                modification.written_by, modification.writes
+            );
+        }
+        let untracked = emulator.untracked_code_modifications();
+        if untracked > 0 {
+            eprintln!("{untracked} more writes over code at other addresses");
+        }
+    }
+
+    Ok(())
 }
@@ -0,0 +1,226 @@
+// Framebuffer to RGBA images for frontends and screenshots. Pixel values index
+// the palette, so 2, 4 and 16 color palettes all work. Phosphor decay fades
+// pixels out over several frames instead of at once, which hides the flicker
+// of games that erase and redraw their sprites every frame.
+mod tests;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::emulator::framebuffer::Framebuffer;
+use crate::emulator::platform::{Pixel, PixelState};
+
+pub type Color = [u8; 3];
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ColorPalette {
+    colors: Vec<Color>,
+}
+
+impl Default for ColorPalette {
+    fn default() -> Self {
+        Self::monochrome([0x00, 0x00, 0x00], [0xff, 0xff, 0xff])
+    }
+}
+
+impl ColorPalette {
+    // 2, 4 or 16 colors, background first
+    pub fn new(colors: &[Color]) -> Option<Self> {
+        match colors.len() {
+            2 | 4 | 16 => Some(ColorPalette {
+                colors: colors.to_vec(),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn monochrome(off: Color, on: Color) -> Self {
+        ColorPalette {
+            colors: vec![off, on],
+        }
+    }
+
+    // Octo's default XO-CHIP colors, for the four combinations of two planes
+    pub fn xochip() -> Self {
+        ColorPalette {
+            colors: vec![
+                [0x99, 0x66, 0x00],
+                [0xff, 0xcc, 0x00],
+                [0xff, 0x66, 0x00],
+                [0x66, 0x22, 0x00],
+            ],
+        }
+    }
+
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    // Pixel values past the end wrap around
+    pub fn color(&self, index: u8) -> Color {
+        self.colors[index as usize % self.colors.len()]
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    // Row major, 4 bytes per pixel
+    pub rgba: Vec<u8>,
+}
+
+impl Image {
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let offset = (y * self.width + x) * 4;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.rgba[offset..offset + 4]);
+        pixel
+    }
+}
+
+pub struct Renderer {
+    palette: ColorPalette,
+    scale: usize,
+    // Out of 256, how much of the previous color a dark pixel keeps each frame
+    persistence: u16,
+    phosphor: Vec<Color>,
+}
+
+impl Renderer {
+    pub fn new(palette: ColorPalette) -> Self {
+        Renderer {
+            palette,
+            scale: 1,
+            persistence: 0,
+            phosphor: Vec::new(),
+        }
+    }
+
+    pub fn with_scale(mut self, scale: u16) -> Self {
+        self.scale = scale.max(1) as usize;
+        self
+    }
+
+    // Fraction of the previous frame's color kept by pixels that went dark,
+    // 0 turns decay off and values near 1 fade slowly
+    pub fn with_phosphor_decay(mut self, persistence: f32) -> Self {
+        self.set_phosphor_decay(persistence);
+        self
+    }
+
+    pub fn set_phosphor_decay(&mut self, persistence: f32) {
+        self.persistence = (persistence.clamp(0.0, 1.0) * 256.0) as u16;
+    }
+
+    pub fn palette(&self) -> &ColorPalette {
+        &self.palette
+    }
+
+    pub fn set_palette(&mut self, palette: ColorPalette) {
+        self.palette = palette;
+        self.reset();
+    }
+
+    pub fn scale(&self) -> u16 {
+        self.scale as u16
+    }
+
+    // Forgets earlier frames, e.g. after loading another ROM
+    pub fn reset(&mut self) {
+        self.phosphor.clear();
+    }
+
+    // With phosphor decay each call is one frame of fading
+    pub fn render(&mut self, framebuffer: &Framebuffer) -> Image {
+        let width = framebuffer.width() as usize;
+        let height = framebuffer.height() as usize;
+        let pixels = framebuffer.as_bytes();
+
+        if self.phosphor.len() != pixels.len() {
+            self.phosphor = pixels
+                .iter()
+                .map(|&pixel| self.palette.color(pixel))
+                .collect();
+        }
+        for (shown, &pixel) in self.phosphor.iter_mut().zip(pixels) {
+            let target = self.palette.color(pixel);
+            *shown = if pixel == 0 {
+                fade(*shown, target, self.persistence)
+            } else {
+                target
+            };
+        }
+
+        let scaled_width = width * self.scale;
+        let mut rgba = Vec::with_capacity(scaled_width * height * self.scale * 4);
+        for row in self.phosphor.chunks(width) {
+            let start = rgba.len();
+            for color in row {
+                for _ in 0..self.scale {
+                    rgba.extend_from_slice(color);
+                    rgba.push(0xff);
+                }
+            }
+            for _ in 1..self.scale {
+                rgba.extend_from_within(start..start + scaled_width * 4);
+            }
+        }
+
+        Image {
+            width: scaled_width,
+            height: height * self.scale,
+            rgba,
+        }
+    }
+}
+
+// Shows the union of the last two frames, so sprites that are erased in one
+// frame and redrawn in the next never disappear
+#[derive(Debug, Clone, Default)]
+pub struct FrameBlender {
+    previous: Option<Framebuffer>,
+}
+
+impl FrameBlender {
+    pub fn new() -> Self {
+        FrameBlender { previous: None }
+    }
+
+    // Call once per 60 Hz frame
+    pub fn next_frame(&mut self, current: &Framebuffer) -> Framebuffer {
+        let blended = self.blend(current);
+        self.previous = Some(current.clone());
+        blended
+    }
+
+    // Without moving on to the next frame, e.g. for a screenshot mid frame
+    pub fn blend(&self, current: &Framebuffer) -> Framebuffer {
+        let mut blended = current.clone();
+        let Some(previous) = &self.previous else {
+            return blended;
+        };
+        for row in 0..current.height() {
+            for column in 0..current.width() {
+                let pixel = Pixel { column, row };
+                if previous.get_pixel(pixel) == PixelState::On {
+                    blended.set_pixel(pixel, PixelState::On);
+                }
+            }
+        }
+        blended
+    }
+
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+}
+
+fn fade(shown: Color, target: Color, persistence: u16) -> Color {
+    let mut faded = target;
+    for ((faded, shown), target) in faded.iter_mut().zip(shown).zip(target) {
+        let difference = shown as i32 - target as i32;
+        *faded = (target as i32 + difference * persistence as i32 / 256) as u8;
+    }
+    faded
+}
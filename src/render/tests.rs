@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod test {
+    use crate::emulator::platform::{Pixel, PixelState};
+    use crate::render::*;
+
+    const OFF: Color = [0x10, 0x20, 0x30];
+    const ON: Color = [0xf0, 0xe0, 0xd0];
+
+    fn framebuffer_with_pixel(column: u8, row: u8) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new();
+        framebuffer.set_pixel(Pixel { column, row }, PixelState::On);
+        framebuffer
+    }
+
+    #[test]
+    fn palettes_have_two_four_or_sixteen_colors() {
+        assert!(ColorPalette::new(&[OFF, ON]).is_some());
+        assert!(ColorPalette::new(&[OFF; 4]).is_some());
+        assert!(ColorPalette::new(&[OFF; 16]).is_some());
+        assert!(ColorPalette::new(&[OFF; 3]).is_none());
+
+        let xochip = ColorPalette::xochip();
+        assert_eq!(xochip.colors().len(), 4);
+        assert_eq!(xochip.color(5), xochip.color(1));
+    }
+
+    #[test]
+    fn scales_each_pixel_into_a_square() {
+        let mut renderer = Renderer::new(ColorPalette::monochrome(OFF, ON)).with_scale(3);
+        let image = renderer.render(&framebuffer_with_pixel(1, 0));
+
+        assert_eq!((image.width, image.height), (192, 96));
+        assert_eq!(image.rgba.len(), 192 * 96 * 4);
+        assert_eq!(image.pixel(2, 2), [0x10, 0x20, 0x30, 0xff]);
+        assert_eq!(image.pixel(3, 0), [0xf0, 0xe0, 0xd0, 0xff]);
+        assert_eq!(image.pixel(5, 2), [0xf0, 0xe0, 0xd0, 0xff]);
+        assert_eq!(image.pixel(6, 2), [0x10, 0x20, 0x30, 0xff]);
+        assert_eq!(image.pixel(4, 3), [0x10, 0x20, 0x30, 0xff]);
+    }
+
+    #[test]
+    fn phosphor_decay_fades_pixels_out() {
+        let palette = ColorPalette::monochrome([0, 0, 0], [200, 200, 200]);
+        let mut renderer = Renderer::new(palette).with_phosphor_decay(0.5);
+        let lit = framebuffer_with_pixel(0, 0);
+        let dark = Framebuffer::new();
+
+        assert_eq!(renderer.render(&lit).pixel(0, 0), [200, 200, 200, 0xff]);
+        assert_eq!(renderer.render(&dark).pixel(0, 0), [100, 100, 100, 0xff]);
+        assert_eq!(renderer.render(&dark).pixel(0, 0), [50, 50, 50, 0xff]);
+        // Lighting up is immediate
+        assert_eq!(renderer.render(&lit).pixel(0, 0), [200, 200, 200, 0xff]);
+
+        for _ in 0..16 {
+            renderer.render(&dark);
+        }
+        assert_eq!(renderer.render(&dark).pixel(0, 0), [0, 0, 0, 0xff]);
+    }
+
+    #[test]
+    fn blending_ors_the_last_two_frames() {
+        let first = framebuffer_with_pixel(0, 0);
+        let second = framebuffer_with_pixel(1, 0);
+        let mut blender = FrameBlender::new();
+
+        assert_eq!(blender.next_frame(&first), first);
+        let blended = blender.next_frame(&second);
+        assert_eq!(
+            blended.get_pixel(Pixel { column: 0, row: 0 }),
+            PixelState::On
+        );
+        assert_eq!(
+            blended.get_pixel(Pixel { column: 1, row: 0 }),
+            PixelState::On
+        );
+
+        // The first frame is no longer one of the last two
+        assert_eq!(blender.next_frame(&Framebuffer::new()), second);
+        assert_eq!(blender.blend(&Framebuffer::new()), Framebuffer::new());
+    }
+
+    #[test]
+    fn without_decay_pixels_turn_off_at_once() {
+        let mut renderer = Renderer::new(ColorPalette::default());
+        renderer.render(&framebuffer_with_pixel(0, 0));
+
+        assert_eq!(
+            renderer.render(&Framebuffer::new()).pixel(0, 0),
+            [0, 0, 0, 0xff]
+        );
+    }
+}
@@ -0,0 +1,238 @@
+// Per-ROM presets looked up by SHA-1, read from files in the chip-8-database
+// format (https://github.com/chip-8/chip-8-database). A copy is bundled from
+// data/chip-8-database, replace those files to ship more programs.
+mod tests;
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use derive_more::From;
+use serde::Deserialize;
+
+use crate::emulator::platform::{KeypadNumber, KEYPAD_COUNT};
+use crate::emulator::quirks::Quirks;
+use crate::render::ColorPalette;
+
+const BUNDLED_PROGRAMS: &str = include_str!("../data/chip-8-database/programs.json");
+const BUNDLED_HASHES: &str = include_str!("../data/chip-8-database/sha1-hashes.json");
+const BUNDLED_PLATFORMS: &str = include_str!("../data/chip-8-database/platforms.json");
+
+#[derive(Debug, From)]
+pub enum DatabaseError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl std::fmt::Display for DatabaseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DatabaseError::Io(error) => write!(f, "could not read ROM database: {error}"),
+            DatabaseError::Json(error) => write!(f, "invalid ROM database: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for DatabaseError {}
+
+#[derive(Debug, Deserialize)]
+struct Program {
+    title: String,
+    #[serde(default)]
+    roms: HashMap<String, Rom>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Rom {
+    #[serde(default)]
+    platforms: Vec<String>,
+    #[serde(default)]
+    quirky_platforms: HashMap<String, QuirkOverrides>,
+    tickrate: Option<u32>,
+    #[serde(default)]
+    keys: BTreeMap<String, u8>,
+    colors: Option<Colors>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Colors {
+    #[serde(default)]
+    pixels: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlatformDefinition {
+    id: String,
+    default_tickrate: u32,
+    #[serde(default)]
+    quirks: QuirkOverrides,
+}
+
+// Every quirk is optional so that ROM entries can override single quirks
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct QuirkOverrides {
+    shift: Option<bool>,
+    memory_increment_by_x: Option<bool>,
+    memory_leave_i_unchanged: Option<bool>,
+    wrap: Option<bool>,
+    jump: Option<bool>,
+    vblank: Option<bool>,
+    logic: Option<bool>,
+}
+
+impl QuirkOverrides {
+    fn apply(&self, quirks: &mut Quirks) {
+        let overrides = [
+            (&mut quirks.shift, self.shift),
+            (
+                &mut quirks.memory_increment_by_x,
+                self.memory_increment_by_x,
+            ),
+            (
+                &mut quirks.memory_leave_i_unchanged,
+                self.memory_leave_i_unchanged,
+            ),
+            (&mut quirks.wrap, self.wrap),
+            (&mut quirks.jump, self.jump),
+            (&mut quirks.vblank, self.vblank),
+            (&mut quirks.logic, self.logic),
+        ];
+        for (quirk, value) in overrides {
+            if let Some(value) = value {
+                *quirk = value;
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RomPreset {
+    pub title: Option<String>,
+    pub platform: String,
+    pub quirks: Quirks,
+    pub instructions_per_frame: u32,
+    // Named game controls ("up", "a", ...) to the keypad key they use
+    pub keys: BTreeMap<String, KeypadNumber>,
+    pub palette: Option<ColorPalette>,
+}
+
+pub struct RomDatabase {
+    programs: Vec<Program>,
+    hashes: HashMap<String, usize>,
+    platforms: Vec<PlatformDefinition>,
+}
+
+impl RomDatabase {
+    pub fn bundled() -> Self {
+        Self::from_json(BUNDLED_PROGRAMS, BUNDLED_HASHES, BUNDLED_PLATFORMS)
+            .expect("bundled ROM database is valid")
+    }
+
+    pub fn from_json(programs: &str, hashes: &str, platforms: &str) -> Result<Self, DatabaseError> {
+        Ok(RomDatabase {
+            programs: serde_json::from_str(programs)?,
+            hashes: serde_json::from_str(hashes)?,
+            platforms: serde_json::from_str(platforms)?,
+        })
+    }
+
+    pub fn load_dir<P: AsRef<Path>>(path: P) -> Result<Self, DatabaseError> {
+        let path = path.as_ref();
+        let programs = std::fs::read_to_string(path.join("programs.json"))?;
+        let hashes = std::fs::read_to_string(path.join("sha1-hashes.json"))?;
+        let platforms = std::fs::read_to_string(path.join("platforms.json"))?;
+        Self::from_json(&programs, &hashes, &platforms)
+    }
+
+    pub fn lookup(&self, rom: &[u8]) -> Option<RomPreset> {
+        let hash = sha1_hex(rom);
+        let program = self.programs.get(*self.hashes.get(&hash)?)?;
+        let rom = program.roms.get(&hash)?;
+
+        // The first listed platform this emulator has a definition for
+        let (platform_id, mut preset) = rom
+            .platforms
+            .iter()
+            .find_map(|id| Some((id, self.platform_preset(id)?)))?;
+        preset.title = Some(program.title.clone());
+
+        if let Some(overrides) = rom.quirky_platforms.get(platform_id) {
+            overrides.apply(&mut preset.quirks);
+        }
+        if let Some(tickrate) = rom.tickrate {
+            preset.instructions_per_frame = tickrate;
+        }
+        // Keys past the keypad would panic when pressed, so they are dropped
+        preset.keys = rom
+            .keys
+            .iter()
+            .filter(|(_, &key)| key < KEYPAD_COUNT)
+            .map(|(name, &key)| (name.clone(), KeypadNumber(key)))
+            .collect();
+        preset.palette = rom.colors.as_ref().and_then(Colors::palette);
+
+        Some(preset)
+    }
+
+    pub fn platform_preset(&self, platform_id: &str) -> Option<RomPreset> {
+        let platform = self
+            .platforms
+            .iter()
+            .find(|platform| platform.id == platform_id)?;
+
+        let mut quirks = Quirks::default();
+        platform.quirks.apply(&mut quirks);
+
+        Some(RomPreset {
+            title: None,
+            platform: platform.id.clone(),
+            quirks,
+            instructions_per_frame: platform.default_tickrate,
+            keys: BTreeMap::new(),
+            palette: None,
+        })
+    }
+}
+
+impl Colors {
+    // Other counts than 2, 4 or 16 only keep the first two colors
+    fn palette(&self) -> Option<ColorPalette> {
+        let colors = self
+            .pixels
+            .iter()
+            .map(|color| parse_color(color))
+            .collect::<Option<Vec<_>>>()?;
+        match colors.as_slice() {
+            [off, on, ..] => {
+                ColorPalette::new(&colors).or(Some(ColorPalette::monochrome(*off, *on)))
+            }
+            _ => None,
+        }
+    }
+}
+
+// "#rrggbb" or the "#rgb" shorthand
+pub fn parse_color(color: &str) -> Option<[u8; 3]> {
+    let digits = color.strip_prefix('#').filter(|digits| digits.is_ascii())?;
+    let channel = |hex: &str| u8::from_str_radix(hex, 16).ok();
+
+    match digits.len() {
+        6 => Some([
+            channel(&digits[0..2])?,
+            channel(&digits[2..4])?,
+            channel(&digits[4..6])?,
+        ]),
+        3 => Some([
+            channel(&digits[0..1])? * 0x11,
+            channel(&digits[1..2])? * 0x11,
+            channel(&digits[2..3])? * 0x11,
+        ]),
+        _ => None,
+    }
+}
+
+pub fn sha1_hex(data: &[u8]) -> String {
+    sha1_smol::Sha1::from(data).digest().to_string()
+}
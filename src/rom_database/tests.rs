@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod test {
+    use crate::rom_database::*;
+
+    // Jump to 0x200 forever
+    const ROM: &[u8] = &[0x12, 0x00];
+    const ROM_HASH: &str = "92a5652d382a18e89c4881ec57041fc7d885ca80";
+
+    const PROGRAMS: &str = r##"[
+        {
+            "title": "Spin",
+            "authors": ["Someone"],
+            "roms": {
+                "92a5652d382a18e89c4881ec57041fc7d885ca80": {
+                    "file": "spin.ch8",
+                    "platforms": ["superchip", "xochip"],
+                    "quirkyPlatforms": { "superchip": { "wrap": true, "jump": false } },
+                    "tickrate": 20,
+                    "keys": { "up": 5, "a": 10, "b": 16 },
+                    "colors": { "pixels": ["#112233", "#fff"], "buzzer": "#ff0000" }
+                }
+            }
+        }
+    ]"##;
+    const HASHES: &str = r#"{ "92a5652d382a18e89c4881ec57041fc7d885ca80": 0 }"#;
+
+    fn test_database() -> RomDatabase {
+        let platforms = include_str!("../../data/chip-8-database/platforms.json");
+        RomDatabase::from_json(PROGRAMS, HASHES, platforms).unwrap()
+    }
+
+    #[test]
+    fn sha1_matches_database_keys() {
+        assert_eq!(sha1_hex(ROM), ROM_HASH);
+    }
+
+    #[test]
+    fn bundled_database_is_valid() {
+        let database = RomDatabase::bundled();
+
+        let preset = database.platform_preset("originalChip8").unwrap();
+        assert_eq!(preset.quirks, Quirks::cosmac_vip());
+    }
+
+    #[test]
+    fn lookup_applies_platform_and_rom_overrides() {
+        let preset = test_database().lookup(ROM).unwrap();
+
+        let expected_quirks = Quirks {
+            wrap: true,
+            jump: false,
+            ..Quirks::superchip()
+        };
+        assert_eq!(preset.title.as_deref(), Some("Spin"));
+        assert_eq!(preset.platform, "superchip");
+        assert_eq!(preset.quirks, expected_quirks);
+        assert_eq!(preset.instructions_per_frame, 20);
+        assert_eq!(preset.keys["up"], KeypadNumber(5));
+        assert_eq!(preset.keys["a"], KeypadNumber(10));
+        assert!(!preset.keys.contains_key("b"));
+        assert_eq!(
+            preset.palette,
+            Some(ColorPalette::monochrome(
+                [0x11, 0x22, 0x33],
+                [0xff, 0xff, 0xff]
+            ))
+        );
+    }
+
+    #[test]
+    fn bundled_database_knows_the_ibm_logo() {
+        let rom = include_bytes!("../emulator/test-dependencies/ibm-logo.ch8");
+        let preset = RomDatabase::bundled().lookup(rom).unwrap();
+
+        assert_eq!(preset.title.as_deref(), Some("IBM Logo"));
+        assert_eq!(preset.platform, "originalChip8");
+        assert_eq!(preset.quirks, Quirks::cosmac_vip());
+        assert_eq!(preset.instructions_per_frame, 15);
+    }
+
+    #[test]
+    fn lookup_skips_platforms_without_a_definition() {
+        let programs = PROGRAMS.replace(
+            r#""platforms": ["superchip", "xochip"]"#,
+            r#""platforms": ["megachip8", "superchip", "xochip"]"#,
+        );
+        let platforms = include_str!("../../data/chip-8-database/platforms.json");
+        let database = RomDatabase::from_json(&programs, HASHES, platforms).unwrap();
+
+        let preset = database.lookup(ROM).unwrap();
+        assert_eq!(preset.platform, "superchip");
+        assert!(preset.quirks.wrap);
+    }
+
+    #[test]
+    fn lookup_unknown_rom() {
+        assert_eq!(test_database().lookup(&[0x00, 0xE0]), None);
+    }
+
+    #[test]
+    fn invalid_colors_are_rejected() {
+        assert_eq!(parse_color("112233"), None);
+        assert_eq!(parse_color("#12345"), None);
+        assert_eq!(parse_color("#gggggg"), None);
+        assert_eq!(parse_color("#ééé"), None);
+    }
+}
@@ -0,0 +1,162 @@
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+use wasm_bindgen::prelude::*;
+
+use crate::emulator::framebuffer::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use crate::emulator::platform::headless::HeadlessPlatform;
+use crate::emulator::platform::*;
+use crate::emulator::{Emulator, MAX_ROM_SIZE};
+use crate::keymap::{Keymap, MappedKeypad};
+use crate::render::{ColorPalette, FrameBlender, Renderer};
+
+#[wasm_bindgen]
+pub struct Chip8 {
+    emulator: Emulator<HeadlessPlatform>,
+    keypad: MappedKeypad,
+    renderer: Renderer,
+    blender: Option<FrameBlender>,
+    seed: u32,
+}
+
+#[wasm_bindgen]
+impl Chip8 {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u32) -> Chip8 {
+        Chip8 {
+            emulator: Emulator::new(HeadlessPlatform::new(seed)),
+            keypad: MappedKeypad::default(),
+            renderer: Renderer::new(ColorPalette::default()),
+            blender: None,
+            seed,
+        }
+    }
+
+    pub fn load_rom(&mut self, rom: &[u8]) -> Result<(), JsError> {
+        if rom.len() > MAX_ROM_SIZE {
+            return Err(JsError::new("ROM does not fit in memory"));
+        }
+
+        self.emulator = Emulator::new(HeadlessPlatform::new(self.seed));
+        self.renderer.reset();
+        if let Some(blender) = &mut self.blender {
+            blender.reset();
+        }
+        run_to_completion(self.emulator.load_into_memory(rom))
+            .map_err(|fault| JsError::new(&fault.to_string()))?;
+        Ok(())
+    }
+
+    pub fn step_frame(&mut self) -> Result<(), JsError> {
+        run_to_completion(self.emulator.run_frame())
+            .map_err(|fault| JsError::new(&fault.to_string()))
+    }
+
+    pub fn set_key(&mut self, key: u8, pressed: bool) -> Result<(), JsError> {
+        if key >= KEYPAD_COUNT {
+            return Err(JsError::new("Key number must be between 0x0 and 0xF"));
+        }
+
+        let state = if pressed { KeyState::On } else { KeyState::Off };
+        self.emulator
+            .platform_mut()
+            .set_key(KeypadNumber(key), state);
+        Ok(())
+    }
+
+    // KeyboardEvent.key for keys with a single character, false if it is not bound
+    pub fn set_host_key(&mut self, key: char, pressed: bool) -> bool {
+        let number = if pressed {
+            self.keypad.key_down(key)
+        } else {
+            self.keypad.key_up(key)
+        };
+        let Some(number) = number else {
+            return false;
+        };
+        self.emulator
+            .platform_mut()
+            .set_key(number, self.keypad.state(number));
+        true
+    }
+
+    pub fn set_keymap(&mut self, preset: &str) -> Result<(), JsError> {
+        let keymap = Keymap::preset(preset).ok_or_else(|| JsError::new("Unknown keymap preset"))?;
+        self.keypad.release_all();
+        self.keypad.set_keymap(keymap);
+        for number in (0..KEYPAD_COUNT).map(KeypadNumber) {
+            self.emulator.platform_mut().set_key(number, KeyState::Off);
+        }
+        Ok(())
+    }
+
+    pub fn buzzer(&self) -> bool {
+        self.emulator.platform().buzzer() == BuzzerState::On
+    }
+
+    pub fn display_width(&self) -> u8 {
+        DISPLAY_WIDTH
+    }
+
+    pub fn display_height(&self) -> u8 {
+        DISPLAY_HEIGHT
+    }
+
+    // One byte per pixel, row major, 1 for on and 0 for off
+    pub fn framebuffer(&self) -> *const u8 {
+        self.emulator.platform().framebuffer().as_bytes().as_ptr()
+    }
+
+    pub fn framebuffer_len(&self) -> usize {
+        self.emulator.platform().framebuffer().as_bytes().len()
+    }
+
+    // Colors as 0xRRGGBB, 2, 4 or 16 of them
+    pub fn set_palette(&mut self, colors: &[u32]) -> Result<(), JsError> {
+        let colors: Vec<[u8; 3]> = colors
+            .iter()
+            .map(|color| {
+                let [_, red, green, blue] = color.to_be_bytes();
+                [red, green, blue]
+            })
+            .collect();
+        let palette =
+            ColorPalette::new(&colors).ok_or_else(|| JsError::new("Expected 2, 4 or 16 colors"))?;
+        self.renderer.set_palette(palette);
+        Ok(())
+    }
+
+    // From 0 to 1, see Renderer::with_phosphor_decay
+    pub fn set_phosphor_decay(&mut self, persistence: f32) {
+        self.renderer.set_phosphor_decay(persistence);
+    }
+
+    // Shows each frame together with the one before it
+    pub fn set_frame_blending(&mut self, enabled: bool) {
+        self.blender = enabled.then(FrameBlender::new);
+    }
+
+    // RGBA for an ImageData, call once per frame for phosphor decay and
+    // frame blending to work
+    pub fn render_rgba(&mut self) -> Vec<u8> {
+        let framebuffer = self.emulator.platform().framebuffer();
+        match &mut self.blender {
+            Some(blender) => self.renderer.render(&blender.next_frame(framebuffer)).rgba,
+            None => self.renderer.render(framebuffer).rgba,
+        }
+    }
+}
+
+// Platform futures never suspend, so polling until ready does not spin
+fn run_to_completion<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
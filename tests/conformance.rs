@@ -0,0 +1,258 @@
+// Walks the whole 16 bit opcode space and checks every value decodes to the
+// documented instruction, or is rejected, for the instruction set the decoder
+// implements. Rows are tried in order so specific encodings come before the
+// ones they overlap with.
+use chip8_rs::emulator::instruction::parser::NomDecoder;
+use chip8_rs::emulator::instruction::table::TableDecoder;
+use chip8_rs::emulator::instruction::{Decoder, Instruction, InstructionSet};
+use chip8_rs::emulator::types::*;
+
+use InstructionSet::*;
+
+struct Encoding {
+    mask: u16,
+    pattern: u16,
+    instruction_set: InstructionSet,
+    mnemonic: &'static str,
+    // None until the emulator has an Instruction for it
+    instruction: Option<fn(u16) -> Instruction>,
+}
+
+const fn encoding(
+    mask: u16,
+    pattern: u16,
+    instruction_set: InstructionSet,
+    mnemonic: &'static str,
+    instruction: Option<fn(u16) -> Instruction>,
+) -> Encoding {
+    Encoding {
+        mask,
+        pattern,
+        instruction_set,
+        mnemonic,
+        instruction,
+    }
+}
+
+fn x(opcode: u16) -> RegisterNumber {
+    RegisterNumber(FourBitValue((opcode >> 8) as u8 & 0xF))
+}
+fn y(opcode: u16) -> RegisterNumber {
+    RegisterNumber(FourBitValue((opcode >> 4) as u8 & 0xF))
+}
+fn n(opcode: u16) -> FourBitValue {
+    FourBitValue(opcode as u8 & 0xF)
+}
+fn nn(opcode: u16) -> EightBitValue {
+    EightBitValue(opcode as u8)
+}
+fn nnn(opcode: u16) -> TwelveBitValue {
+    TwelveBitValue(opcode & 0xFFF)
+}
+
+const EXACT: u16 = 0xFFFF;
+const NIBBLE_0: u16 = 0xF000;
+const NIBBLES_0_3: u16 = 0xF00F;
+const NIBBLES_0_2_3: u16 = 0xF0FF;
+const NIBBLES_0_1_2: u16 = 0xFFF0;
+
+#[rustfmt::skip]
+const ENCODINGS: &[Encoding] = &[
+    // SUPER-CHIP and XO-CHIP additions that overlap with 0NNN
+    encoding(NIBBLES_0_1_2, 0x00C0, SuperChip, "00CN scroll down", None),
+    encoding(NIBBLES_0_1_2, 0x00D0, XoChip, "00DN scroll up", None),
+    encoding(EXACT, 0x00FB, SuperChip, "00FB scroll right", None),
+    encoding(EXACT, 0x00FC, SuperChip, "00FC scroll left", None),
+    encoding(EXACT, 0x00FD, SuperChip, "00FD exit", None),
+    encoding(EXACT, 0x00FE, SuperChip, "00FE low resolution", None),
+    encoding(EXACT, 0x00FF, SuperChip, "00FF high resolution", None),
+
+    encoding(EXACT, 0x00E0, Chip8, "00E0 clear", Some(|_| Instruction::ClearDisplay)),
+    encoding(EXACT, 0x00EE, Chip8, "00EE return", Some(|_| Instruction::ReturnFromSubroutine)),
+    encoding(NIBBLE_0, 0x0000, Chip8, "0NNN machine code", Some(|op| Instruction::System { address: MemoryAddress(nnn(op)) })),
+    encoding(NIBBLE_0, 0x1000, Chip8, "1NNN jump", Some(|op| Instruction::Jump { address: MemoryAddress(nnn(op)) })),
+    encoding(NIBBLE_0, 0x2000, Chip8, "2NNN call", Some(|op| Instruction::Call { address: MemoryAddress(nnn(op)) })),
+    encoding(NIBBLE_0, 0x3000, Chip8, "3XNN skip if equal", Some(|op| Instruction::SkipNextInstructionIfMatch { read_value_from: x(op), immediate: nn(op) })),
+    encoding(NIBBLE_0, 0x4000, Chip8, "4XNN skip if not equal", Some(|op| Instruction::SkipNextInstructionIfNotMatch { read_value_from: x(op), immediate: nn(op) })),
+    encoding(NIBBLES_0_3, 0x5000, Chip8, "5XY0 skip if registers equal", Some(|op| Instruction::SkipNextInstructionIfValuesMatch { lhs: x(op), rhs: y(op) })),
+    encoding(NIBBLES_0_3, 0x5002, XoChip, "5XY2 store range", None),
+    encoding(NIBBLES_0_3, 0x5003, XoChip, "5XY3 load range", None),
+    encoding(NIBBLE_0, 0x6000, Chip8, "6XNN load", Some(|op| Instruction::LoadImmediateToRegister { immediate: nn(op), destination: x(op) })),
+    encoding(NIBBLE_0, 0x7000, Chip8, "7XNN add", Some(|op| Instruction::AddImmediateToRegister { immediate: nn(op), destination: x(op) })),
+    encoding(NIBBLES_0_3, 0x8000, Chip8, "8XY0 copy", Some(|op| Instruction::CopyRegisterValue { source: y(op), destination: x(op) })),
+    encoding(NIBBLES_0_3, 0x8001, Chip8, "8XY1 or", Some(|op| Instruction::BitwiseOrRegisters { source: y(op), destination: x(op) })),
+    encoding(NIBBLES_0_3, 0x8002, Chip8, "8XY2 and", Some(|op| Instruction::BitwiseAndRegisters { source: y(op), destination: x(op) })),
+    encoding(NIBBLES_0_3, 0x8003, Chip8, "8XY3 xor", Some(|op| Instruction::BitwiseXorRegisters { source: y(op), destination: x(op) })),
+    encoding(NIBBLES_0_3, 0x8004, Chip8, "8XY4 add", Some(|op| Instruction::AddRegisters { source: y(op), destination: x(op) })),
+    encoding(NIBBLES_0_3, 0x8005, Chip8, "8XY5 subtract", Some(|op| Instruction::SubtractSourceFromDestination { source: y(op), destination: x(op) })),
+    encoding(NIBBLES_0_3, 0x8006, Chip8, "8XY6 shift right", Some(|op| Instruction::ShiftRightRegisters { source: y(op), destination: x(op) })),
+    encoding(NIBBLES_0_3, 0x8007, Chip8, "8XY7 reverse subtract", Some(|op| Instruction::SubtractDestinationFromSource { source: y(op), destination: x(op) })),
+    encoding(NIBBLES_0_3, 0x800E, Chip8, "8XYE shift left", Some(|op| Instruction::ShiftLeftRegisters { source: y(op), destination: x(op) })),
+    encoding(NIBBLES_0_3, 0x9000, Chip8, "9XY0 skip if registers differ", Some(|op| Instruction::SkipNextInstructionIfValuesDoNotMatch { lhs: x(op), rhs: y(op) })),
+    encoding(NIBBLE_0, 0xA000, Chip8, "ANNN load I", Some(|op| Instruction::LoadToIRegister { immediate: nnn(op) })),
+    encoding(NIBBLE_0, 0xB000, Chip8, "BNNN jump with offset", Some(|op| Instruction::JumpToSumOfV0ValueAndImmediate { immediate: nnn(op) })),
+    encoding(NIBBLE_0, 0xC000, Chip8, "CXNN random", Some(|op| Instruction::LoadBitwiseAndOfRandomByteAndImmediate { destination: x(op), immediate: nn(op) })),
+    encoding(NIBBLES_0_3, 0xD000, SuperChip, "DXY0 draw 16x16", None),
+    encoding(NIBBLE_0, 0xD000, Chip8, "DXYN draw", Some(|op| Instruction::DrawSpritesFromMemory { read_x_axis_from: x(op), read_y_axis_from: y(op), bytes_to_read_from_i_register: n(op) })),
+    encoding(NIBBLES_0_2_3, 0xE09E, Chip8, "EX9E skip if key", Some(|op| Instruction::SkipNextInstructionIfKeyIsPressed { read_key_number_from: x(op) })),
+    encoding(NIBBLES_0_2_3, 0xE0A1, Chip8, "EXA1 skip if not key", Some(|op| Instruction::SkipNextInstructionIfKeyIsNotPressed { read_key_number_from: x(op) })),
+    encoding(EXACT, 0xF000, XoChip, "F000 long load I", None),
+    encoding(EXACT, 0xF002, XoChip, "F002 load audio pattern", None),
+    encoding(NIBBLES_0_2_3, 0xF001, XoChip, "FN01 select planes", None),
+    encoding(NIBBLES_0_2_3, 0xF007, Chip8, "FX07 read delay timer", Some(|op| Instruction::LoadDelayTimerIntoRegister { destination: x(op) })),
+    encoding(NIBBLES_0_2_3, 0xF00A, Chip8, "FX0A wait for key", Some(|op| Instruction::AwaitKeyPressAndLoadIntoRegister { destination: x(op) })),
+    encoding(NIBBLES_0_2_3, 0xF015, Chip8, "FX15 set delay timer", Some(|op| Instruction::LoadIntoDelayTimer { source: x(op) })),
+    encoding(NIBBLES_0_2_3, 0xF018, Chip8, "FX18 set sound timer", Some(|op| Instruction::LoadIntoSoundTimer { source: x(op) })),
+    encoding(NIBBLES_0_2_3, 0xF01E, Chip8, "FX1E add to I", Some(|op| Instruction::AddValueToIRegister { source: x(op) })),
+    encoding(NIBBLES_0_2_3, 0xF029, Chip8, "FX29 font character", Some(|op| Instruction::LoadSpriteLocationForValueIntoIRegister { source: x(op) })),
+    encoding(NIBBLES_0_2_3, 0xF030, SuperChip, "FX30 large font character", None),
+    encoding(NIBBLES_0_2_3, 0xF033, Chip8, "FX33 binary coded decimal", Some(|op| Instruction::LoadBinaryCodedDecimalValueIntoSequenceStartingAtIRegisterValue { source: x(op) })),
+    encoding(NIBBLES_0_2_3, 0xF03A, XoChip, "FX3A audio pitch", None),
+    encoding(NIBBLES_0_2_3, 0xF055, Chip8, "FX55 store registers", Some(|op| Instruction::LoadValuesFromV0ToRegisterIntoSequenceStartingAtIRegisterValue { end: x(op) })),
+    encoding(NIBBLES_0_2_3, 0xF065, Chip8, "FX65 load registers", Some(|op| Instruction::LoadSequenceStartingAtIRegisterValueIntoV0ToRegister { end: x(op) })),
+    encoding(NIBBLES_0_2_3, 0xF075, SuperChip, "FX75 save flags", None),
+    encoding(NIBBLES_0_2_3, 0xF085, SuperChip, "FX85 load flags", None),
+];
+
+fn documented_encoding(opcode: u16, instruction_set: InstructionSet) -> Option<&'static Encoding> {
+    ENCODINGS.iter().find(|encoding| {
+        encoding.instruction_set <= instruction_set && opcode & encoding.mask == encoding.pattern
+    })
+}
+
+fn check_opcode_space(decoder: &dyn Decoder) -> usize {
+    let instruction_set = decoder.instruction_set();
+    let mut valid_opcodes = 0;
+
+    for opcode in 0..=u16::MAX {
+        let expected = documented_encoding(opcode, instruction_set).map(|encoding| {
+            let instruction = encoding.instruction.unwrap_or_else(|| {
+                panic!(
+                    "{:?} decoder includes {}, which has no Instruction",
+                    instruction_set, encoding.mnemonic
+                )
+            });
+            (encoding.mnemonic, instruction(opcode))
+        });
+        let decoded = decoder.decode(&opcode.to_be_bytes());
+
+        match expected {
+            Some((mnemonic, instruction)) => {
+                assert_eq!(
+                    decoded,
+                    Some(instruction),
+                    "{opcode:04X} should be {mnemonic}"
+                );
+                valid_opcodes += 1;
+            }
+            None => assert_eq!(decoded, None, "{opcode:04X} should be invalid"),
+        }
+    }
+    valid_opcodes
+}
+
+// 0NNN, 1NNN to 4NNN, 6XNN, 7XNN and ANNN to DXYN take any operands, 5XY0,
+// 9XY0, the nine 8XY_ and the eleven EX__/FX__ opcodes only their own low bits
+const CHIP8_VALID_OPCODES: usize = 0x1000 * 11 + 0x100 * (2 + 9) + 0x10 * (2 + 9);
+
+// Opcodes whose documented encoding is a row of this instruction set
+fn added_opcodes(instruction_set: InstructionSet) -> impl Iterator<Item = u16> {
+    (0..=u16::MAX).filter(move |&opcode| {
+        documented_encoding(opcode, instruction_set)
+            .is_some_and(|encoding| encoding.instruction_set == instruction_set)
+    })
+}
+
+// 00CN, 00FB to 00FF, DXY0, FX30, FX75 and FX85
+const SUPERCHIP_ADDED_OPCODES: usize = 0x10 + 5 + 0x100 + 0x10 * 3;
+// 00DN, 5XY2, 5XY3, F000, F002, FN01 and FX3A
+const XOCHIP_ADDED_OPCODES: usize = 0x10 + 0x100 * 2 + 2 + 0x10 * 2;
+
+#[test]
+fn nom_decoder_matches_documented_encodings() {
+    assert_eq!(check_opcode_space(&NomDecoder), CHIP8_VALID_OPCODES);
+}
+
+#[test]
+fn table_decoder_matches_documented_encodings() {
+    assert_eq!(check_opcode_space(&TableDecoder), CHIP8_VALID_OPCODES);
+}
+
+#[test]
+fn system_does_not_swallow_clear_and_return() {
+    for decoder in [&NomDecoder as &dyn Decoder, &TableDecoder] {
+        assert_eq!(
+            decoder.decode(&[0x00, 0xE0]),
+            Some(Instruction::ClearDisplay)
+        );
+        assert_eq!(
+            decoder.decode(&[0x00, 0xEE]),
+            Some(Instruction::ReturnFromSubroutine)
+        );
+    }
+}
+
+#[test]
+fn invalid_chip8_encodings_are_rejected() {
+    for opcode in [
+        0x5121u16, 0x9121, 0x8128, 0x812F, 0xE19F, 0xF108, 0xF130, 0xF175,
+    ] {
+        assert_eq!(
+            documented_encoding(opcode, Chip8).map(|encoding| encoding.mnemonic),
+            None
+        );
+        for decoder in [&NomDecoder as &dyn Decoder, &TableDecoder] {
+            assert_eq!(decoder.decode(&opcode.to_be_bytes()), None, "{opcode:04X}");
+        }
+    }
+}
+
+#[test]
+fn later_instruction_sets_add_their_documented_opcodes() {
+    assert_eq!(added_opcodes(Chip8).count(), CHIP8_VALID_OPCODES);
+    assert_eq!(added_opcodes(SuperChip).count(), SUPERCHIP_ADDED_OPCODES);
+    assert_eq!(added_opcodes(XoChip).count(), XOCHIP_ADDED_OPCODES);
+}
+
+#[test]
+fn chip8_decoders_do_not_decode_later_additions() {
+    for instruction_set in [SuperChip, XoChip] {
+        for opcode in added_opcodes(instruction_set) {
+            // Overlapping additions such as 00FF keep their CHIP-8 meaning
+            let expected = documented_encoding(opcode, Chip8)
+                .map(|encoding| (encoding.instruction.unwrap())(opcode));
+            for decoder in [&NomDecoder as &dyn Decoder, &TableDecoder] {
+                assert_eq!(
+                    decoder.decode(&opcode.to_be_bytes()),
+                    expected,
+                    "{opcode:04X} is {instruction_set:?}"
+                );
+            }
+        }
+    }
+}
+
+// Stands in for a decoder of a later instruction set until one exists
+struct Claiming(InstructionSet);
+
+impl Decoder for Claiming {
+    fn decode(&self, opcode: &[u8; 2]) -> Option<Instruction> {
+        TableDecoder.decode(opcode)
+    }
+
+    fn instruction_set(&self) -> InstructionSet {
+        self.0
+    }
+}
+
+#[test]
+#[should_panic(expected = "SuperChip decoder includes 00CN scroll down")]
+fn superchip_decoder_is_checked_against_superchip_rows() {
+    check_opcode_space(&Claiming(SuperChip));
+}
+
+#[test]
+#[should_panic(expected = "XoChip decoder includes 00CN scroll down")]
+fn xochip_decoder_is_checked_against_xochip_rows() {
+    check_opcode_space(&Claiming(XoChip));
+}
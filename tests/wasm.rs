@@ -0,0 +1,69 @@
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use chip8_rs::wasm::Chip8;
+use wasm_bindgen_test::*;
+
+// V0 = 0, I = 0x208, draw one row at (V0, V0) then loop forever
+const DRAW_PIXEL_ROM: &[u8] = &[0x60, 0x00, 0xA2, 0x08, 0xD0, 0x01, 0x12, 0x06, 0x80];
+
+fn framebuffer(chip8: &Chip8) -> &[u8] {
+    unsafe { core::slice::from_raw_parts(chip8.framebuffer(), chip8.framebuffer_len()) }
+}
+
+#[wasm_bindgen_test]
+fn framebuffer_starts_blank() {
+    let chip8 = Chip8::new(1);
+
+    assert_eq!(chip8.framebuffer_len(), 64 * 32);
+    assert!(framebuffer(&chip8).iter().all(|&pixel| pixel == 0));
+}
+
+#[wasm_bindgen_test]
+fn step_frame_draws_into_framebuffer() {
+    let mut chip8 = Chip8::new(1);
+    chip8.load_rom(DRAW_PIXEL_ROM).unwrap();
+
+    chip8.step_frame().unwrap();
+
+    assert_eq!(framebuffer(&chip8)[0], 1);
+}
+
+#[wasm_bindgen_test]
+fn load_rom_rejects_oversized_rom() {
+    let mut chip8 = Chip8::new(1);
+
+    assert!(chip8.load_rom(&[0; 4096]).is_err());
+}
+
+#[wasm_bindgen_test]
+fn set_key_rejects_invalid_key() {
+    let mut chip8 = Chip8::new(1);
+
+    assert!(chip8.set_key(0xF, true).is_ok());
+    assert!(chip8.set_key(0x10, true).is_err());
+}
+
+#[wasm_bindgen_test]
+fn host_keys_follow_the_keymap() {
+    let mut chip8 = Chip8::new(1);
+
+    assert!(chip8.set_host_key('q', true));
+    assert!(!chip8.set_host_key('y', true));
+    assert!(chip8.set_keymap("azerty").is_ok());
+    assert!(chip8.set_host_key('a', true));
+    assert!(chip8.set_keymap("colemak").is_err());
+}
+
+#[wasm_bindgen_test]
+fn render_rgba_uses_palette() {
+    let mut chip8 = Chip8::new(1);
+    chip8.load_rom(DRAW_PIXEL_ROM).unwrap();
+    chip8.step_frame().unwrap();
+
+    assert!(chip8.set_palette(&[0x000000, 0x00ff00, 0xff0000]).is_err());
+    chip8.set_palette(&[0x102030, 0x00ff00]).unwrap();
+    let rgba = chip8.render_rgba();
+
+    assert_eq!(rgba.len(), 64 * 32 * 4);
+    assert_eq!(rgba[..8], [0x00, 0xff, 0x00, 0xff, 0x10, 0x20, 0x30, 0xff]);
+}